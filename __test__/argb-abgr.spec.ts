@@ -0,0 +1,78 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+import { generateQuadrantImage } from "./test-utils.js";
+
+it("toBufferSync - argb moves alpha to the front", () => {
+  const src = Buffer.from([10, 20, 30, 40, 50, 60, 70, 80]);
+
+  const result = ImageTransformer.fromBuffer(src, 2, 1, "rgba").toBufferSync(
+    "argb"
+  );
+
+  expect(Array.from(result.buffer)).toEqual([40, 10, 20, 30, 80, 50, 60, 70]);
+});
+
+it("toBufferSync - abgr reverses the channels", () => {
+  const src = Buffer.from([10, 20, 30, 40, 50, 60, 70, 80]);
+
+  const result = ImageTransformer.fromBuffer(src, 2, 1, "rgba").toBufferSync(
+    "abgr"
+  );
+
+  expect(Array.from(result.buffer)).toEqual([40, 30, 20, 10, 80, 70, 60, 50]);
+});
+
+it("fromBuffer - argb input", () => {
+  const src = Buffer.from([40, 10, 20, 30, 80, 50, 60, 70]);
+
+  const result = ImageTransformer.fromBuffer(src, 2, 1, "argb").toBufferSync(
+    "rgba"
+  );
+
+  expect(Array.from(result.buffer)).toEqual([10, 20, 30, 40, 50, 60, 70, 80]);
+});
+
+it("fromBuffer - abgr input", () => {
+  const src = Buffer.from([40, 30, 20, 10, 80, 70, 60, 50]);
+
+  const result = ImageTransformer.fromBuffer(src, 2, 1, "abgr").toBufferSync(
+    "rgba"
+  );
+
+  expect(Array.from(result.buffer)).toEqual([10, 20, 30, 40, 50, 60, 70, 80]);
+});
+
+it("argb - round trip through transforms", () => {
+  const width = 8;
+  const height = 6;
+  const rgba = generateQuadrantImage(width, height);
+  const argb = ImageTransformer.fromBuffer(
+    rgba,
+    width,
+    height,
+    "rgba"
+  ).toBufferSync("argb");
+
+  const result = ImageTransformer.fromBuffer(argb.buffer, width, height, "argb")
+    .flipVertical()
+    .flipVertical()
+    .toBufferSync("rgba");
+
+  expect(Array.from(result.buffer)).toEqual(Array.from(rgba));
+});
+
+it("pad + format conversion - padding colour in abgr order", () => {
+  const padColor = { red: 10, green: 20, blue: 30, alpha: 128 };
+
+  const result = ImageTransformer.fromBuffer(
+    Buffer.from([255, 0, 0, 255]),
+    1,
+    1,
+    "rgba"
+  )
+    .pad(1, 0, 0, 0, padColor)
+    .toBufferSync("abgr");
+
+  expect(result.width).toBe(2);
+  expect(Array.from(result.buffer)).toEqual([128, 30, 20, 10, 255, 0, 0, 255]);
+});
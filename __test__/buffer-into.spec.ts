@@ -0,0 +1,128 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+import { generateQuadrantImage } from "./test-utils.js";
+
+it("toBufferIntoSync - matches toBufferSync", () => {
+  const width = 8;
+  const height = 6;
+  const transformer = ImageTransformer.fromBuffer(
+    generateQuadrantImage(width, height),
+    width,
+    height,
+    "rgba"
+  ).flipHorizontal();
+
+  const target = Buffer.alloc(width * height * 3);
+  const info = transformer.toBufferIntoSync(target, "bgr");
+
+  expect(info).toEqual({ width, height });
+  expect(Array.from(target)).toEqual(
+    Array.from(transformer.toBufferSync("bgr").buffer)
+  );
+});
+
+it("toBufferInto - writes at an offset", async () => {
+  const src = Buffer.from([1, 2, 3, 4, 5, 6, 7, 8]);
+  const transformer = ImageTransformer.fromBuffer(src, 2, 1, "rgba");
+
+  const target = new Uint8Array(12).fill(0xff);
+  const info = await transformer.toBufferInto(target, "rgba", 2);
+
+  expect(info).toEqual({ width: 2, height: 1 });
+  expect(Array.from(target)).toEqual([
+    0xff, 0xff, 1, 2, 3, 4, 5, 6, 7, 8, 0xff, 0xff,
+  ]);
+});
+
+it("toBufferInto - draws into part of a larger frame", async () => {
+  // A 2x2 image placed at (1, 1) in a 4x3 luma frame
+  const src = Buffer.from([10, 20, 30, 40]);
+  const transformer = ImageTransformer.fromBuffer(src, 2, 2, "luma");
+
+  const frame = Buffer.alloc(4 * 3, 0xaa);
+  await transformer.toBufferInto(frame, "luma", 4 + 1, { stride: 4 });
+
+  // The rest of the frame is left untouched
+  expect(Array.from(frame)).toEqual([
+    0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 10, 20, 0xaa, 0xaa, 30, 40, 0xaa,
+  ]);
+});
+
+it("toBufferInto - final row padding is not needed", async () => {
+  const transformer = ImageTransformer.fromBuffer(
+    Buffer.from([10, 20, 30, 40]),
+    2,
+    2,
+    "luma"
+  );
+
+  // The image ends at the last byte of the frame
+  const frame = Buffer.alloc(4 * 2);
+  await transformer.toBufferInto(frame, "luma", 2, { stride: 4 });
+
+  expect(Array.from(frame)).toEqual([0, 0, 10, 20, 0, 0, 30, 40]);
+});
+
+it("toBufferIntoSync - target too small", () => {
+  const transformer = ImageTransformer.fromBuffer(
+    Buffer.alloc(16),
+    2,
+    2,
+    "rgba"
+  );
+
+  expect(() => transformer.toBufferIntoSync(Buffer.alloc(15), "rgba")).toThrow(
+    "Target buffer is too small"
+  );
+  expect(() =>
+    transformer.toBufferIntoSync(Buffer.alloc(16), "rgba", 1)
+  ).toThrow("Target buffer is too small");
+});
+
+it("toBufferInto - rejects when the target is too small", async () => {
+  const transformer = ImageTransformer.fromBuffer(
+    Buffer.alloc(16),
+    2,
+    2,
+    "rgba"
+  );
+
+  await expect(
+    transformer.toBufferInto(Buffer.alloc(12), "rgb", 4)
+  ).rejects.toThrow("Target buffer is too small");
+});
+
+it("toBufferInto - renders into separate parts of one frame at once", async () => {
+  // Eight 6x5 tiles side by side in a 48x5 rgb frame, each a different colour
+  const tile = { width: 6, height: 5 };
+  const frameWidth = tile.width * 8;
+  const frame = Buffer.alloc(frameWidth * tile.height * 3);
+
+  const colors = Array.from({ length: 8 }, (_, i) => [
+    i * 30,
+    255 - i * 30,
+    i,
+  ]);
+  const infos = await Promise.all(
+    colors.map((color, i) =>
+      ImageTransformer.fromBuffer(
+        Buffer.alloc(tile.width * tile.height * 3).map((_, j) => color[j % 3]),
+        tile.width,
+        tile.height,
+        "rgb"
+      ).toBufferInto(frame, "rgb", i * tile.width * 3, {
+        stride: frameWidth * 3,
+      })
+    )
+  );
+
+  expect(infos).toEqual(colors.map(() => tile));
+  for (let y = 0; y < tile.height; y++) {
+    for (let x = 0; x < frameWidth; x++) {
+      const offset = (y * frameWidth + x) * 3;
+      expect(Array.from(frame.subarray(offset, offset + 3))).toEqual(
+        colors[Math.floor(x / tile.width)]
+      );
+    }
+  }
+});
@@ -5,6 +5,12 @@ import {
   generateGradientImage,
   generateCheckerboardImage,
   generateQuadrantImage,
+  withExifOrientation,
+  assertImagesSimilar,
+  calculatePixelDifference,
+  createTestPattern,
+  encodeUncompressedImage,
+  wrapPngInIco,
 } from "./test-utils.js";
 import fs from "fs/promises";
 import path from "path";
@@ -611,3 +617,563 @@ it("data URL round trip - complete cycle", () => {
   expect(result.width).toBe(7);
   expect(result.height).toBe(7);
 });
+
+it("fromEncodedImage - applies EXIF orientation", () => {
+  const size = { width: 32, height: 16 };
+  const original = generateQuadrantImage(size.width, size.height);
+
+  const jpeg = ImageTransformer.fromBuffer(
+    original,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("jpeg", { quality: 1 }).buffer;
+
+  // Orientation 6 means the image must be rotated 90 degrees clockwise to display
+  const rotated = withExifOrientation(jpeg, 6);
+
+  const transformer = ImageTransformer.fromEncodedImage(rotated);
+  expect(transformer.getCurrentDimensions()).toEqual({
+    width: size.height,
+    height: size.width,
+  });
+
+  const result = transformer.toBufferSync("rgb");
+  expect(result.width).toBe(size.height);
+  expect(result.height).toBe(size.width);
+
+  // The bottom-left (blue) quadrant is now in the top-left corner
+  const topLeft = Array.from(result.buffer.subarray(0, 3));
+  expect(topLeft[0]).toBeLessThan(16);
+  expect(topLeft[1]).toBeLessThan(16);
+  expect(topLeft[2]).toBeGreaterThan(240);
+});
+
+it("fromEncodedImage - EXIF orientation can be ignored", () => {
+  const size = { width: 32, height: 16 };
+  const original = generateQuadrantImage(size.width, size.height);
+
+  const jpeg = ImageTransformer.fromBuffer(
+    original,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("jpeg", { quality: 1 }).buffer;
+  const rotated = withExifOrientation(jpeg, 6);
+
+  const transformer = ImageTransformer.fromEncodedImage(rotated, {
+    applyOrientation: false,
+  });
+  expect(transformer.getCurrentDimensions()).toEqual(size);
+
+  const result = transformer.toBufferSync("rgb");
+  expect(result.width).toBe(size.width);
+  expect(result.height).toBe(size.height);
+
+  // The top-left quadrant is still red
+  const topLeft = Array.from(result.buffer.subarray(0, 3));
+  expect(topLeft[0]).toBeGreaterThan(240);
+  expect(topLeft[1]).toBeLessThan(16);
+  expect(topLeft[2]).toBeLessThan(16);
+});
+
+it("fromImageDataUrl - applies EXIF orientation", () => {
+  const size = { width: 32, height: 16 };
+  const original = generateQuadrantImage(size.width, size.height);
+
+  const jpeg = ImageTransformer.fromBuffer(
+    original,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("jpeg", { quality: 1 }).buffer;
+  const dataUrl = `data:image/jpeg;base64,${withExifOrientation(jpeg, 8).toString("base64")}`;
+
+  const result = ImageTransformer.fromImageDataUrl(dataUrl).toBufferSync("rgb");
+  expect(result.width).toBe(size.height);
+  expect(result.height).toBe(size.width);
+});
+
+for (const format of ["pnm", "tga", "bmp", "qoi", "tiff"] as const) {
+  it(`fromEncodedImage - decodes ${format}`, () => {
+    const size = { width: 6, height: 4 };
+    const rgb = generateQuadrantImage(size.width, size.height, "rgb");
+    const encoded = encodeUncompressedImage(
+      format,
+      size.width,
+      size.height,
+      rgb
+    );
+
+    const transformer = ImageTransformer.fromEncodedImage(encoded);
+    expect(transformer.getSourceFormat()).toBe(format);
+    expect(transformer.getCurrentDimensions()).toEqual(size);
+
+    const result = transformer.toBufferSync("rgb");
+    expect(result.width).toBe(size.width);
+    expect(result.height).toBe(size.height);
+    expect(Array.from(result.buffer)).toEqual(Array.from(rgb));
+  });
+}
+
+it("fromEncodedImage - rejects data that isn't a plausible TGA file", () => {
+  const size = { width: 6, height: 4 };
+  const rgb = generateQuadrantImage(size.width, size.height, "rgb");
+  const tga = encodeUncompressedImage("tga", size.width, size.height, rgb);
+
+  const text = Buffer.from("This is a plain text file, not an image at all");
+  const truncated = tga.subarray(0, tga.length - 1);
+  const zeroWidth = Buffer.from(tga);
+  zeroWidth.writeUInt16LE(0, 12);
+  const badImageType = Buffer.from(tga);
+  badImageType[2] = 7;
+
+  for (const data of [text, truncated, zeroWidth, badImageType]) {
+    expect(() => ImageTransformer.fromEncodedImage(data)).toThrow(
+      "Failed to determine image dimensions"
+    );
+  }
+});
+
+it("fromEncodedImage - decodes ico", () => {
+  const size = { width: 16, height: 16 };
+  const rgba = generateQuadrantImage(size.width, size.height);
+  const png = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("png").buffer;
+
+  const transformer = ImageTransformer.fromEncodedImage(
+    wrapPngInIco(png, size.width, size.height)
+  );
+  expect(transformer.getSourceFormat()).toBe("ico");
+
+  const result = transformer.toBufferSync("rgba");
+  expect(result.width).toBe(size.width);
+  expect(result.height).toBe(size.height);
+  expect(Array.from(result.buffer)).toEqual(Array.from(rgba));
+});
+
+it("getSourceFormat - reports the detected format", () => {
+  const size = { width: 8, height: 8 };
+  const source = ImageTransformer.fromBuffer(
+    generateSolidColorImage(size.width, size.height, 10, 20, 30),
+    size.width,
+    size.height,
+    "rgba"
+  );
+  expect(source.getSourceFormat()).toBeNull();
+
+  const jpeg = source.toEncodedImageSync("jpeg").buffer;
+  expect(ImageTransformer.fromEncodedImage(jpeg).getSourceFormat()).toBe(
+    "jpeg"
+  );
+
+  const dataUrl = source.toDataUrlSync("webp");
+  expect(ImageTransformer.fromImageDataUrl(dataUrl).getSourceFormat()).toBe(
+    "webp"
+  );
+});
+
+for (const format of ["bmp", "tiff", "qoi"] as const) {
+  it(`toEncodedImageSync - ${format} round trip`, () => {
+    const size = { width: 10, height: 6 };
+    const rgba = generateQuadrantImage(size.width, size.height);
+
+    const encoded = ImageTransformer.fromBuffer(
+      rgba,
+      size.width,
+      size.height,
+      "rgba"
+    ).toEncodedImageSync(format);
+    expect(encoded.width).toBe(size.width);
+    expect(encoded.height).toBe(size.height);
+
+    const decoded = ImageTransformer.fromEncodedImage(encoded.buffer);
+    expect(decoded.getSourceFormat()).toBe(format);
+
+    const result = decoded.toBufferSync("rgba");
+    expect(Array.from(result.buffer)).toEqual(Array.from(rgba));
+  });
+}
+
+it("toEncodedImageSync - gif is quantised to a palette", () => {
+  const size = { width: 16, height: 16 };
+  const rgba = generateQuadrantImage(size.width, size.height);
+
+  const encoded = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("gif");
+  expect(encoded.buffer.subarray(0, 6).toString("ascii")).toBe("GIF89a");
+
+  const decoded = ImageTransformer.fromEncodedImage(encoded.buffer);
+  expect(decoded.getSourceFormat()).toBe("gif");
+
+  // Only four colours, so the palette can hold them exactly
+  const result = decoded.toBufferSync("rgba");
+  assertImagesSimilar(result.buffer, rgba, size.width, size.height, "rgba", 4);
+});
+
+it("toEncodedImageSync - ico contains each of the requested sizes", () => {
+  const size = { width: 64, height: 32 };
+  const rgba = generateQuadrantImage(size.width, size.height);
+
+  const encoded = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("ico", { icoSizes: [16, 32, 64] });
+
+  // ICONDIR header, followed by one 16 byte entry per image
+  expect(encoded.buffer.readUInt16LE(2)).toBe(1);
+  expect(encoded.buffer.readUInt16LE(4)).toBe(3);
+  const entries = [0, 1, 2].map((i) => [
+    encoded.buffer[6 + i * 16],
+    encoded.buffer[7 + i * 16],
+  ]);
+  expect(entries).toEqual([
+    [16, 8],
+    [32, 16],
+    [64, 32],
+  ]);
+
+  // The largest image is the one that gets decoded
+  const decoded = ImageTransformer.fromEncodedImage(encoded.buffer);
+  expect(decoded.getSourceFormat()).toBe("ico");
+  expect(decoded.getCurrentDimensions()).toEqual(size);
+});
+
+it("toEncodedImageSync - ico rejects empty and out of range sizes", () => {
+  const transformer = ImageTransformer.fromBuffer(
+    generateSolidColorImage(8, 8, 0, 0, 0),
+    8,
+    8,
+    "rgba"
+  );
+
+  expect(() =>
+    transformer.toEncodedImageSync("ico", { icoSizes: [512] })
+  ).toThrow("icoSizes must be between 1 and 256");
+  expect(() =>
+    transformer.toEncodedImageSync("ico", { icoSizes: [16, 257] })
+  ).toThrow("icoSizes must be between 1 and 256");
+  expect(() =>
+    transformer.toEncodedImageSync("ico", { icoSizes: [0] })
+  ).toThrow("icoSizes must be between 1 and 256");
+  expect(() => transformer.toEncodedImageSync("ico", { icoSizes: [] })).toThrow(
+    "icoSizes must contain at least one size"
+  );
+});
+
+it("toDataUrl - uses the mime type of the format", async () => {
+  const transformer = ImageTransformer.fromBuffer(
+    generateSolidColorImage(4, 4, 0, 0, 0),
+    4,
+    4,
+    "rgba"
+  );
+
+  expect(await transformer.toDataUrl("bmp")).toMatch(
+    /^data:image\/bmp;base64,/
+  );
+  expect(await transformer.toDataUrl("ico")).toMatch(
+    /^data:image\/x-icon;base64,/
+  );
+});
+
+it("toEncodedImageSync - webp is lossy when a quality is given", () => {
+  // Detailed content, which lossless compression handles poorly
+  const size = { width: 96, height: 96 };
+  const rgb = Buffer.alloc(size.width * size.height * 3);
+  for (let y = 0; y < size.height; y++) {
+    for (let x = 0; x < size.width; x++) {
+      const offset = (y * size.width + x) * 3;
+      rgb[offset] = 128 + 127 * Math.sin(x / 4 + y / 7);
+      rgb[offset + 1] = 128 + 127 * Math.sin(y / 3);
+      rgb[offset + 2] = 128 + 127 * Math.cos(x / 5);
+    }
+  }
+  const transformer = ImageTransformer.fromBuffer(
+    rgb,
+    size.width,
+    size.height,
+    "rgb"
+  );
+
+  const lossless = transformer.toEncodedImageSync("webp");
+  const lossy = transformer.toEncodedImageSync("webp", { quality: 0.5 });
+  expect(lossy.buffer.length).toBeLessThan(lossless.buffer.length / 2);
+
+  // The lossy bitstream uses a VP8 chunk, where lossless uses VP8L
+  expect(lossless.buffer.subarray(12, 16).toString("ascii")).toBe("VP8L");
+  expect(lossy.buffer.subarray(12, 16).toString("ascii")).toBe("VP8 ");
+
+  const decoded = ImageTransformer.fromEncodedImage(
+    lossy.buffer
+  ).toBufferSync("rgb");
+  expect(decoded.width).toBe(size.width);
+  expect(decoded.height).toBe(size.height);
+
+  const diff = calculatePixelDifference(
+    decoded.buffer,
+    rgb,
+    size.width,
+    size.height,
+    "rgb"
+  );
+  expect(diff.difference).toBeLessThan(10);
+});
+
+it("toEncodedImageSync - webp lossless flag overrides quality", () => {
+  const size = { width: 16, height: 16 };
+  const rgba = generateQuadrantImage(size.width, size.height);
+  const transformer = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  );
+
+  const lossless = transformer.toEncodedImageSync("webp", {
+    quality: 0.5,
+    lossless: true,
+  });
+  const decoded = ImageTransformer.fromEncodedImage(
+    lossless.buffer
+  ).toBufferSync("rgba");
+  expect(Array.from(decoded.buffer)).toEqual(Array.from(rgba));
+
+  const lossy = transformer.toEncodedImageSync("webp", { lossless: false });
+  expect(lossy.buffer.subarray(12, 16).toString("ascii")).not.toBe("VP8L");
+});
+
+// AVIF support is an opt-in cargo feature, so is not in every build
+const avifEnabled = (() => {
+  try {
+    ImageTransformer.fromBuffer(
+      generateSolidColorImage(1, 1, 0, 0, 0),
+      1,
+      1,
+      "rgba"
+    ).toEncodedImageSync("avif", { speed: 10 });
+    return true;
+  } catch {
+    return false;
+  }
+})();
+
+it.skipIf(!avifEnabled)("toEncodedImageSync - AVIF output", () => {
+  const size = { width: 32, height: 32 };
+  const transformer = ImageTransformer.fromBuffer(
+    createTestPattern(size.width, size.height),
+    size.width,
+    size.height,
+    "rgba"
+  );
+
+  const high = transformer.toEncodedImageSync("avif", {
+    quality: 0.9,
+    speed: 10,
+  });
+  const low = transformer.toEncodedImageSync("avif", {
+    quality: 0.2,
+    speed: 10,
+  });
+  expect(high.width).toBe(size.width);
+  expect(high.height).toBe(size.height);
+  expect(low.buffer.length).toBeLessThan(high.buffer.length);
+
+  // ISOBMFF file type box
+  expect(high.buffer.subarray(4, 12).toString("ascii")).toBe("ftypavif");
+});
+
+it("toEncodedImageSync - png compression level", () => {
+  const size = { width: 64, height: 64 };
+  const rgba = createTestPattern(size.width, size.height);
+  const transformer = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  );
+
+  const uncompressed = transformer.toEncodedImageSync("png", {
+    compressionLevel: 0,
+  });
+  const best = transformer.toEncodedImageSync("png", {
+    compressionLevel: 9,
+    pngFilter: "Paeth",
+  });
+  expect(best.buffer.length).toBeLessThan(uncompressed.buffer.length);
+  expect(uncompressed.buffer.length).toBeGreaterThan(rgba.length);
+
+  for (const result of [uncompressed, best]) {
+    const decoded = ImageTransformer.fromEncodedImage(
+      result.buffer
+    ).toBufferSync("rgba");
+    expect(Array.from(decoded.buffer)).toEqual(Array.from(rgba));
+  }
+});
+
+it("toEncodedImageSync - indexed png keeps up to 256 colours exactly", () => {
+  const size = { width: 16, height: 16 };
+  const rgba = generateQuadrantImage(size.width, size.height);
+  // Make one of the colours translucent
+  for (let i = 3; i < rgba.length; i += 4 * size.width) {
+    rgba[i] = 128;
+  }
+
+  const result = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("png", { indexed: true });
+
+  // IHDR bit depth and colour type (3 = indexed)
+  expect(result.buffer[24]).toBe(8);
+  expect(result.buffer[25]).toBe(3);
+
+  const decoded = ImageTransformer.fromEncodedImage(
+    result.buffer
+  ).toBufferSync("rgba");
+  expect(Array.from(decoded.buffer)).toEqual(Array.from(rgba));
+});
+
+it("toEncodedImageSync - indexed png quantises many colours", () => {
+  const size = { width: 64, height: 64 };
+  const rgba = createTestPattern(size.width, size.height);
+
+  const result = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  ).toEncodedImageSync("png", { indexed: true });
+  expect(result.buffer[25]).toBe(3);
+
+  const decoded = ImageTransformer.fromEncodedImage(
+    result.buffer
+  ).toBufferSync("rgba");
+  const diff = calculatePixelDifference(
+    decoded.buffer,
+    rgba,
+    size.width,
+    size.height
+  );
+  expect(diff.difference).toBeLessThan(4);
+});
+
+/** Offset of the first occurrence of a JPEG marker, such as 0xc0 for a baseline frame header */
+function findJpegMarker(jpeg: Buffer, marker: number): number {
+  for (let i = 0; i < jpeg.length - 1; i++) {
+    if (jpeg[i] === 0xff && jpeg[i + 1] === marker) return i;
+  }
+  return -1;
+}
+
+it("toEncodedImageSync - jpeg chroma subsampling", () => {
+  const size = { width: 64, height: 64 };
+  const transformer = ImageTransformer.fromBuffer(
+    createTestPattern(size.width, size.height),
+    size.width,
+    size.height,
+    "rgba"
+  );
+
+  const expectedSampling = { "4:4:4": 0x11, "4:2:2": 0x21, "4:2:0": 0x22 };
+  const lengths: number[] = [];
+  for (const [subsampling, sampling] of Object.entries(expectedSampling)) {
+    const result = transformer.toEncodedImageSync("jpeg", {
+      subsampling: subsampling as "4:4:4" | "4:2:2" | "4:2:0",
+    });
+    lengths.push(result.buffer.length);
+
+    // Sampling factors of the luma component in the frame header
+    const frame = findJpegMarker(result.buffer, 0xc0);
+    expect(frame).toBeGreaterThan(0);
+    expect(result.buffer[frame + 11]).toBe(sampling);
+  }
+
+  // Less chroma data gives smaller files
+  expect(lengths[1]).toBeLessThan(lengths[0]);
+  expect(lengths[2]).toBeLessThan(lengths[1]);
+});
+
+it("toEncodedImageSync - progressive jpeg", () => {
+  const size = { width: 32, height: 32 };
+  const rgba = createTestPattern(size.width, size.height);
+  const transformer = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  );
+
+  const baseline = transformer.toEncodedImageSync("jpeg").buffer;
+  expect(findJpegMarker(baseline, 0xc0)).toBeGreaterThan(0);
+  expect(findJpegMarker(baseline, 0xc2)).toBe(-1);
+
+  const progressive = transformer.toEncodedImageSync("jpeg", {
+    progressive: true,
+    quality: 0.95,
+  }).buffer;
+  expect(findJpegMarker(progressive, 0xc2)).toBeGreaterThan(0);
+
+  const decoded = ImageTransformer.fromEncodedImage(progressive).toBufferSync(
+    "rgba"
+  );
+  assertImagesSimilar(
+    decoded.buffer,
+    rgba,
+    size.width,
+    size.height,
+    "rgba",
+    16
+  );
+});
+
+it("toEncodedImageSync - jpeg background flattens transparency", () => {
+  const size = { width: 16, height: 16 };
+  // Transparent red on the left, opaque blue on the right
+  const rgba = generateSolidColorImage(size.width, size.height, 255, 0, 0, 0);
+  for (let y = 0; y < size.height; y++) {
+    for (let x = size.width / 2; x < size.width; x++) {
+      rgba.set([0, 0, 255, 255], (y * size.width + x) * 4);
+    }
+  }
+  const transformer = ImageTransformer.fromBuffer(
+    rgba,
+    size.width,
+    size.height,
+    "rgba"
+  );
+
+  const pixelAt = (jpeg: Buffer, x: number, y: number) => {
+    const decoded = ImageTransformer.fromEncodedImage(jpeg).toBufferSync("rgb");
+    const offset = (y * size.width + x) * 3;
+    return Array.from(decoded.buffer.subarray(offset, offset + 3));
+  };
+  const expectColor = (actual: number[], expected: number[]) => {
+    actual.forEach((value, i) => {
+      expect(Math.abs(value - expected[i])).toBeLessThan(8);
+    });
+  };
+
+  // Without a background the colour of the transparent pixels shows through
+  const dropped = transformer.toEncodedImageSync("jpeg", { quality: 1 });
+  expectColor(pixelAt(dropped.buffer, 2, 8), [255, 0, 0]);
+
+  const flattened = transformer.toEncodedImageSync("jpeg", {
+    quality: 1,
+    background: { red: 255, green: 255, blue: 255, alpha: 255 },
+  });
+  expectColor(pixelAt(flattened.buffer, 2, 8), [255, 255, 255]);
+  expectColor(pixelAt(flattened.buffer, 13, 8), [0, 0, 255]);
+});
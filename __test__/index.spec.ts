@@ -6,7 +6,9 @@ import {
   generateCheckerboardImage,
   generateQuadrantImage,
   createTestPattern,
+  createHardEdgePattern,
   assertImagesSimilar,
+  encodeRgba16Png,
 } from "./test-utils.js";
 
 // Test data constants
@@ -355,6 +357,112 @@ it("rotate - 270 degrees clockwise", () => {
 });
 
 // Padding tests
+it("rotateDegrees - quarter turns match rotate", () => {
+  const size = { width: 6, height: 4 };
+  const buffer = generateQuadrantImage(size.width, size.height);
+
+  const expected = ImageTransformer.fromBuffer(
+    buffer,
+    size.width,
+    size.height,
+    "rgba"
+  )
+    .rotate("CW90")
+    .toBufferSync("rgba");
+  const result = ImageTransformer.fromBuffer(
+    buffer,
+    size.width,
+    size.height,
+    "rgba"
+  )
+    .rotateDegrees(90, { expand: true })
+    .toBufferSync("rgba");
+
+  expect(result.width).toBe(size.height);
+  expect(result.height).toBe(size.width);
+  expect(result.buffer.equals(expected.buffer)).toBe(true);
+
+  // A negative angle is the same as rotating the other way
+  const negative = ImageTransformer.fromBuffer(
+    buffer,
+    size.width,
+    size.height,
+    "rgba"
+  )
+    .rotateDegrees(-270, { expand: true })
+    .toBufferSync("rgba");
+  expect(negative.buffer.equals(expected.buffer)).toBe(true);
+});
+
+it("rotateDegrees - expand grows the canvas to the rotated bounds", () => {
+  const size = { width: 20, height: 10 };
+  const buffer = generateSolidColorImage(size.width, size.height, 255, 0, 0);
+  const background = { red: 0, green: 255, blue: 0, alpha: 255 };
+
+  const transformer = ImageTransformer.fromBuffer(
+    buffer,
+    size.width,
+    size.height,
+    "rgba"
+  ).rotateDegrees(45, { expand: true, background });
+
+  // 20*cos(45) + 10*sin(45) = 21.2
+  const expected = { width: 22, height: 22 };
+  expect(transformer.getCurrentDimensions()).toEqual(expected);
+
+  const result = transformer.toBufferSync("rgba");
+  expect(result.width).toBe(expected.width);
+  expect(result.height).toBe(expected.height);
+
+  const pixelAt = (x: number, y: number) => {
+    const offset = (y * expected.width + x) * 4;
+    return Array.from(result.buffer.subarray(offset, offset + 4));
+  };
+
+  // Corners are uncovered, the centre is the image
+  expect(pixelAt(0, 0)).toEqual([0, 255, 0, 255]);
+  expect(pixelAt(21, 21)).toEqual([0, 255, 0, 255]);
+  expect(pixelAt(11, 11)).toEqual([255, 0, 0, 255]);
+});
+
+it("rotateDegrees - keeps the canvas size by default", () => {
+  const size = { width: 16, height: 16 };
+  const buffer = generateSolidColorImage(size.width, size.height, 255, 0, 0);
+
+  for (const interpolation of ["Nearest", "Bilinear", "Bicubic"] as const) {
+    const transformer = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    ).rotateDegrees(30, { interpolation });
+    expect(transformer.getCurrentDimensions()).toEqual(size);
+
+    const result = transformer.toBufferSync("rgba");
+    expect(result.width).toBe(size.width);
+    expect(result.height).toBe(size.height);
+
+    // Corner is transparent, centre is untouched
+    expect(result.buffer[3]).toBe(0);
+    const centre = (8 * size.width + 8) * 4;
+    expect(Array.from(result.buffer.subarray(centre, centre + 4))).toEqual([
+      255, 0, 0, 255,
+    ]);
+  }
+});
+
+it("rotateDegrees - invalid angle", () => {
+  const transformer = ImageTransformer.fromBuffer(
+    generateSolidColorImage(4, 4, 0, 0, 0),
+    4,
+    4,
+    "rgba"
+  );
+
+  expect(() => transformer.rotateDegrees(NaN)).toThrow();
+  expect(() => transformer.rotateDegrees(Infinity)).toThrow();
+});
+
 it("pad - add padding around image", () => {
   const original = TEST_SIZES.small;
   const padding = { left: 5, right: 10, top: 3, bottom: 7 };
@@ -741,6 +849,329 @@ it("resize modes - Fill vs Fit vs Exact behavior verification", () => {
 });
 
 // Test crop boundary conditions
+it("scale - resampling filters", () => {
+  const original = { width: 8, height: 8 };
+  const target = { width: 32, height: 24 };
+  const buffer = generateCheckerboardImage(original.width, original.height, 2);
+
+  for (const filter of [
+    "Nearest",
+    "Triangle",
+    "CatmullRom",
+    "Gaussian",
+    "Lanczos3",
+  ] as const) {
+    const result = ImageTransformer.fromBuffer(
+      buffer,
+      original.width,
+      original.height,
+      "rgba"
+    )
+      .scale(target.width, target.height, "Exact", { filter })
+      .toBufferSync("rgba");
+
+    expect(result.width).toBe(target.width);
+    expect(result.height).toBe(target.height);
+  }
+});
+
+it("scale - nearest filter keeps hard edges", () => {
+  const original = { width: 8, height: 8 };
+  const buffer = generateCheckerboardImage(original.width, original.height, 2);
+
+  const countValues = (filter: "Nearest" | "Lanczos3") => {
+    const result = ImageTransformer.fromBuffer(
+      buffer,
+      original.width,
+      original.height,
+      "rgba"
+    )
+      .scale(32, 32, "Exact", { filter })
+      .toBufferSync("rgb");
+
+    return new Set(result.buffer).size;
+  };
+
+  // Nearest only ever copies source pixels: black and white
+  expect(countValues("Nearest")).toBe(2);
+  // Lanczos3 produces intermediate values along the edges
+  expect(countValues("Lanczos3")).toBeGreaterThan(2);
+});
+
+it("scale - SIMD resize matches image-rs within tolerance", () => {
+  const original = { width: 193, height: 107 };
+
+  for (const [buffer, hardEdges] of [
+    [createTestPattern(original.width, original.height), false],
+    [createHardEdgePattern(original.width, original.height), true],
+  ] as const) {
+    // The same pixels, but decoded as rgba16 which is always resized by image-rs
+    const png16 = encodeRgba16Png(original.width, original.height, buffer);
+
+    for (const filter of [
+      "Nearest",
+      "Triangle",
+      "CatmullRom",
+      "Gaussian",
+      "Lanczos3",
+    ] as const) {
+      for (const target of [
+        { width: 72, height: 72 },
+        { width: 400, height: 211 },
+      ]) {
+        const fast = ImageTransformer.fromBuffer(
+          buffer,
+          original.width,
+          original.height,
+          "rgba"
+        )
+          .scale(target.width, target.height, "Exact", { filter })
+          .toBufferSync("rgba");
+        const reference = ImageTransformer.fromEncodedImage(png16)
+          .scale(target.width, target.height, "Exact", { filter })
+          .toBufferSync("rgba");
+
+        // Filters with negative lobes are clamped between passes by the SIMD
+        // implementation, which shows up next to hard edges
+        const overshoots = filter === "CatmullRom" || filter === "Lanczos3";
+        assertImagesSimilar(
+          fast.buffer,
+          reference.buffer,
+          target.width,
+          target.height,
+          "rgba",
+          hardEdges && overshoots ? 50 : 1
+        );
+      }
+    }
+  }
+});
+
+it("scale - Contain pads to the exact target size", () => {
+  const original = { width: 100, height: 50 };
+  const target = { width: 60, height: 60 };
+  const buffer = generateSolidColorImage(
+    original.width,
+    original.height,
+    255,
+    0,
+    0
+  );
+  const background = { red: 0, green: 0, blue: 255, alpha: 255 };
+
+  const transformer = ImageTransformer.fromBuffer(
+    buffer,
+    original.width,
+    original.height,
+    "rgba"
+  ).scale(target.width, target.height, "Contain", { background });
+
+  expect(transformer.getCurrentDimensions()).toEqual(target);
+
+  const result = transformer.toBufferSync("rgba");
+  expect(result.width).toBe(target.width);
+  expect(result.height).toBe(target.height);
+
+  const pixelAt = (x: number, y: number) => {
+    const offset = (y * target.width + x) * 4;
+    return Array.from(result.buffer.subarray(offset, offset + 4));
+  };
+
+  // Fitted to 60x30, centred vertically with 15px bars above and below
+  expect(pixelAt(30, 5)).toEqual([0, 0, 255, 255]);
+  expect(pixelAt(30, 30)).toEqual([255, 0, 0, 255]);
+  expect(pixelAt(30, 54)).toEqual([0, 0, 255, 255]);
+});
+
+it("scale - Contain honours gravity", () => {
+  const original = { width: 50, height: 100 };
+  const buffer = generateSolidColorImage(
+    original.width,
+    original.height,
+    255,
+    255,
+    255
+  );
+
+  const render = (gravity: "TopLeft" | "Right" | "Center") =>
+    ImageTransformer.fromBuffer(
+      buffer,
+      original.width,
+      original.height,
+      "rgba"
+    )
+      .scale(40, 40, "Contain", { gravity })
+      .toBufferSync("rgba");
+
+  // Fitted to 20x40, leaving 20px of transparent padding horizontally
+  const alphaAt = (result: { buffer: Buffer }, x: number) =>
+    result.buffer[(20 * 40 + x) * 4 + 3];
+
+  const topLeft = render("TopLeft");
+  expect(alphaAt(topLeft, 5)).toBe(255);
+  expect(alphaAt(topLeft, 35)).toBe(0);
+
+  const right = render("Right");
+  expect(alphaAt(right, 5)).toBe(0);
+  expect(alphaAt(right, 35)).toBe(255);
+
+  const center = render("Center");
+  expect(alphaAt(center, 5)).toBe(0);
+  expect(alphaAt(center, 20)).toBe(255);
+  expect(alphaAt(center, 35)).toBe(0);
+});
+
+it("scale - Fill honours gravity", () => {
+  // 2:1 quadrant image, filled into a square keeps one half of the width
+  const original = { width: 100, height: 50 };
+  const buffer = generateQuadrantImage(original.width, original.height);
+
+  const render = (gravity?: "Left" | "Right") =>
+    ImageTransformer.fromBuffer(
+      buffer,
+      original.width,
+      original.height,
+      "rgba"
+    )
+      .scale(20, 20, "Fill", gravity ? { gravity } : undefined)
+      .toBufferSync("rgba");
+
+  const pixelAt = (result: { buffer: Buffer }, x: number, y: number) => {
+    const offset = (y * 20 + x) * 4;
+    return Array.from(result.buffer.subarray(offset, offset + 3));
+  };
+
+  // Left keeps the red (top) and blue (bottom) quadrants
+  const left = render("Left");
+  expect(pixelAt(left, 10, 2)).toEqual([255, 0, 0]);
+  expect(pixelAt(left, 10, 17)).toEqual([0, 0, 255]);
+
+  // Right keeps the green (top) and yellow (bottom) quadrants
+  const right = render("Right");
+  expect(pixelAt(right, 10, 2)).toEqual([0, 255, 0]);
+  expect(pixelAt(right, 10, 17)).toEqual([255, 255, 0]);
+
+  // Center straddles the middle
+  const center = render();
+  expect(pixelAt(center, 2, 2)).toEqual([255, 0, 0]);
+  expect(pixelAt(center, 17, 2)).toEqual([0, 255, 0]);
+});
+
+it("cropCenter - anchored by gravity", () => {
+  const size = { width: 40, height: 40 };
+  const buffer = generateQuadrantImage(size.width, size.height);
+
+  const crop = (
+    gravity: "TopLeft" | "TopRight" | "BottomLeft" | "BottomRight"
+  ) => {
+    const result = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    )
+      .cropCenter(10, 10, gravity)
+      .toBufferSync("rgba");
+    expect(result.width).toBe(10);
+    expect(result.height).toBe(10);
+    return Array.from(result.buffer.subarray(0, 3));
+  };
+
+  expect(crop("TopLeft")).toEqual([255, 0, 0]);
+  expect(crop("TopRight")).toEqual([0, 255, 0]);
+  expect(crop("BottomLeft")).toEqual([0, 0, 255]);
+  expect(crop("BottomRight")).toEqual([255, 255, 0]);
+});
+
+it("smartCrop - keeps the detailed region", () => {
+  const size = { width: 200, height: 100 };
+  const buffer = generateSolidColorImage(size.width, size.height, 128, 128, 128);
+
+  // Place a detailed, colourful patch towards the right hand side
+  for (let y = 30; y < 70; y++) {
+    for (let x = 150; x < 190; x++) {
+      const offset = (y * size.width + x) * 4;
+      const on = (Math.floor(x / 3) + Math.floor(y / 3)) % 2 === 0;
+      buffer[offset] = on ? 255 : 0;
+      buffer[offset + 1] = on ? 64 : 200;
+      buffer[offset + 2] = on ? 0 : 255;
+    }
+  }
+
+  for (const strategy of ["Entropy", "Attention"] as const) {
+    const transformer = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    ).smartCrop(60, 100, strategy);
+
+    expect(transformer.getCurrentDimensions()).toEqual({
+      width: 60,
+      height: 100,
+    });
+
+    const result = transformer.toBufferSync("rgba");
+    expect(result.width).toBe(60);
+    expect(result.height).toBe(100);
+
+    // The output should contain the patch, rather than only the flat grey
+    let patchPixels = 0;
+    for (let i = 0; i < result.buffer.length; i += 4) {
+      if (result.buffer[i] !== 128 || result.buffer[i + 2] !== 128) {
+        patchPixels++;
+      }
+    }
+    expect(patchPixels).toBeGreaterThan(40 * 40 * 0.9);
+  }
+});
+
+it("smartCrop - evenly interesting image falls back to centre", () => {
+  const size = { width: 100, height: 60 };
+  // A grey ramp has the same edge density everywhere
+  const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+  for (let y = 0; y < size.height; y++) {
+    for (let x = 0; x < size.width; x++) {
+      const offset = (y * size.width + x) * 4;
+      buffer[offset] = buffer[offset + 1] = buffer[offset + 2] = x * 2;
+    }
+  }
+
+  const centre = ImageTransformer.fromBuffer(
+    buffer,
+    size.width,
+    size.height,
+    "rgba"
+  )
+    .cropCenter(40, 60)
+    .toBufferSync("rgba");
+
+  const smart = ImageTransformer.fromBuffer(
+    buffer,
+    size.width,
+    size.height,
+    "rgba"
+  )
+    .smartCrop(40, 60, "Attention")
+    .toBufferSync("rgba");
+
+  expect(smart.width).toBe(centre.width);
+  expect(smart.height).toBe(centre.height);
+  expect(smart.buffer.equals(centre.buffer)).toBe(true);
+});
+
+it("smartCrop - invalid dimensions", () => {
+  const transformer = ImageTransformer.fromBuffer(
+    generateSolidColorImage(10, 10, 0, 0, 0),
+    10,
+    10,
+    "rgba"
+  );
+
+  expect(() => transformer.smartCrop(20, 5)).toThrow();
+  expect(() => transformer.smartCrop(0, 5)).toThrow();
+});
+
 it("crop - boundary validation", () => {
   const size = { width: 10, height: 10 };
   const buffer = generateSolidColorImage(
@@ -1168,6 +1599,75 @@ describe("Overlay operations", () => {
     }).toThrow();
   });
 
+  it("should clip overlays at negative positions", () => {
+    // 4x4 black base with a 3x3 white overlay hanging off the top left
+    const base = ImageTransformer.fromBuffer(
+      Buffer.alloc(16, 0),
+      4,
+      4,
+      "luma"
+    );
+    const top = ImageTransformer.fromBuffer(
+      Buffer.alloc(9, 255),
+      3,
+      3,
+      "luma"
+    );
+
+    const result = base.overlay(top, -2, -1).toBufferSync("luma");
+
+    // Only the bottom right 1x2 corner of the overlay is visible
+    expect(Array.from(result.buffer)).toEqual([
+      255, 0, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ]);
+  });
+
+  it("should blend overlays at negative positions", () => {
+    const base = ImageTransformer.fromBuffer(
+      Buffer.from([200, 200, 200, 255, 200, 200, 200, 255]),
+      2,
+      1,
+      "rgba"
+    );
+    const top = ImageTransformer.fromBuffer(
+      Buffer.from([0, 0, 0, 255, 100, 100, 100, 255]),
+      2,
+      1,
+      "rgba"
+    );
+
+    const result = base
+      .overlay(top, -1, 0, { blendMode: "Difference", opacity: 0.5 })
+      .toBufferSync("rgba");
+
+    // The second overlay pixel lands on the first base pixel, and the
+    // second base pixel is left alone
+    expect(Array.from(result.buffer)).toEqual([
+      150, 150, 150, 255, 200, 200, 200, 255,
+    ]);
+  });
+
+  it("should reject overlays that only touch the edge", () => {
+    const base = ImageTransformer.fromBuffer(
+      Buffer.alloc(100, 0),
+      10,
+      10,
+      "luma"
+    );
+    const top = ImageTransformer.fromBuffer(
+      Buffer.alloc(20, 255),
+      5,
+      4,
+      "luma"
+    );
+
+    expect(() => base.overlay(top, -5, 0)).toThrow();
+    expect(() => base.overlay(top, 0, -4)).toThrow();
+    expect(() => base.overlay(top, 10, 0)).toThrow();
+    expect(() => base.overlay(top, 0, 10)).toThrow();
+    expect(() => base.overlay(top, -4, -3)).not.toThrow();
+  });
+
   it("should work with transformed overlays", () => {
     const size = { width: 60, height: 60 };
 
@@ -1222,4 +1722,172 @@ describe("Overlay operations", () => {
     expect(pixels[centerOffset]).toBe(255); // Red from overlay
     expect(pixels[centerOffset + 2]).toBe(0); // Blue should be replaced
   });
+  it("should apply overlay opacity", () => {
+    const base = ImageTransformer.fromBuffer(
+      Buffer.from([255, 0, 0, 255]),
+      1,
+      1,
+      "rgba"
+    );
+    const top = ImageTransformer.fromBuffer(
+      Buffer.from([0, 0, 255, 255]),
+      1,
+      1,
+      "rgba"
+    );
+
+    const result = base
+      .overlay(top, 0, 0, { opacity: 0.5 })
+      .toBufferSync("rgba");
+
+    expect(Array.from(result.buffer)).toEqual([128, 0, 128, 255]);
+  });
+
+  it("should apply overlay blend modes", () => {
+    const bottom = [200, 100, 50, 255];
+    const top = [100, 150, 200, 255];
+    const expected = {
+      Normal: [100, 150, 200],
+      Multiply: [78, 59, 39],
+      Screen: [222, 191, 211],
+      Overlay: [188, 118, 78],
+      Darken: [100, 100, 50],
+      Lighten: [200, 150, 200],
+      Add: [255, 250, 250],
+      Difference: [100, 50, 150],
+      SoftLight: [191, 111, 86],
+    } as const;
+
+    for (const [blendMode, color] of Object.entries(expected)) {
+      const result = ImageTransformer.fromBuffer(
+        Buffer.from(bottom),
+        1,
+        1,
+        "rgba"
+      )
+        .overlay(
+          ImageTransformer.fromBuffer(Buffer.from(top), 1, 1, "rgba"),
+          0,
+          0,
+          { blendMode: blendMode as keyof typeof expected }
+        )
+        .toBufferSync("rgba");
+
+      const pixel = Array.from(result.buffer);
+      color.forEach((value, i) => {
+        expect(Math.abs(pixel[i] - value)).toBeLessThanOrEqual(1);
+      });
+      expect(pixel[3]).toBe(255);
+    }
+  });
+
+  it("should only blend where both images are visible", () => {
+    // A transparent pixel and an opaque pixel below an opaque overlay
+    const base = ImageTransformer.fromBuffer(
+      Buffer.from([0, 0, 0, 0, 200, 200, 200, 255]),
+      2,
+      1,
+      "rgba"
+    );
+    const top = ImageTransformer.fromBuffer(
+      Buffer.from([100, 100, 100, 255, 100, 100, 100, 255]),
+      2,
+      1,
+      "rgba"
+    );
+
+    const result = base
+      .overlay(top, 0, 0, { blendMode: "Multiply" })
+      .toBufferSync("rgba");
+
+    expect(Array.from(result.buffer)).toEqual([
+      100, 100, 100, 255, 78, 78, 78, 255,
+    ]);
+  });
+
+  it("should keep opaque pixels opaque under a translucent overlay", () => {
+    const render = (opacity: number) =>
+      ImageTransformer.fromBuffer(
+        Buffer.from([10, 20, 30, 255]),
+        1,
+        1,
+        "rgba"
+      )
+        .overlay(
+          ImageTransformer.fromBuffer(
+            Buffer.from([200, 100, 50, 128]),
+            1,
+            1,
+            "rgba"
+          ),
+          0,
+          0,
+          { opacity }
+        )
+        .toBufferSync("rgba");
+
+    const full = Array.from(render(1).buffer);
+    const nearlyFull = Array.from(render(0.999).buffer);
+
+    expect(full[3]).toBe(255);
+    full.forEach((value, i) => {
+      expect(Math.abs(value - nearlyFull[i])).toBeLessThanOrEqual(1);
+    });
+  });
+
+  it("should keep the precision of 16-bit images", () => {
+    const src = Buffer.alloc(4);
+    src.writeUInt16LE(0x1235, 0);
+    src.writeUInt16LE(0x1235, 2);
+    const top = ImageTransformer.fromBuffer(
+      Buffer.from([0, 0, 0, 255]),
+      1,
+      1,
+      "rgba"
+    );
+
+    const result = ImageTransformer.fromBuffer(src, 2, 1, "luma16")
+      .overlay(top, 1, 0, { opacity: 0.5 })
+      .toBufferSync("luma16");
+
+    // The first pixel is untouched, and the second is halfway to black
+    const halfway = result.buffer.readUInt16LE(2);
+    expect(result.buffer.readUInt16LE(0)).toBe(0x1235);
+    expect(Math.abs(halfway - 0x1235 / 2)).toBeLessThanOrEqual(1);
+  });
+
+  it("should leave pixels under a transparent overlay unchanged", () => {
+    const base = ImageTransformer.fromBuffer(
+      Buffer.from([10, 20, 30, 0, 40, 50, 60, 128]),
+      2,
+      1,
+      "rgba"
+    );
+    const top = ImageTransformer.fromBuffer(
+      Buffer.from([255, 255, 255, 0, 255, 255, 255, 0]),
+      2,
+      1,
+      "rgba"
+    );
+
+    const result = base
+      .overlay(top, 0, 0, { blendMode: "Multiply" })
+      .toBufferSync("rgba");
+
+    expect(Array.from(result.buffer)).toEqual([
+      10, 20, 30, 0, 40, 50, 60, 128,
+    ]);
+  });
+
+  it("should reject an invalid overlay opacity", () => {
+    const base = ImageTransformer.fromBuffer(Buffer.alloc(4), 1, 1, "rgba");
+    const top = ImageTransformer.fromBuffer(Buffer.alloc(4), 1, 1, "rgba");
+
+    expect(() => base.overlay(top, 0, 0, { opacity: 1.5 })).toThrow(
+      "Opacity must be between 0 and 1"
+    );
+    expect(() => base.overlay(top, 0, 0, { opacity: NaN })).toThrow(
+      "Opacity must be between 0 and 1"
+    );
+  });
 });
@@ -0,0 +1,112 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+import { generateQuadrantImage } from "./test-utils.js";
+
+it("toBufferSync - luma uses Rec. 709 coefficients", () => {
+  const width = 3;
+  const height = 1;
+  // Pure red, green and blue
+  const src = Buffer.from([255, 0, 0, 0, 255, 0, 0, 0, 255]);
+
+  const result = ImageTransformer.fromBuffer(
+    src,
+    width,
+    height,
+    "rgb"
+  ).toBufferSync("luma");
+
+  expect(result.width).toBe(width);
+  expect(result.height).toBe(height);
+  expect(Array.from(result.buffer)).toEqual([54, 182, 18]);
+});
+
+it("toBufferSync - lumaAlpha keeps the alpha channel", () => {
+  const src = Buffer.from([255, 255, 255, 128, 0, 0, 0, 0]);
+
+  const result = ImageTransformer.fromBuffer(src, 2, 1, "rgba").toBufferSync(
+    "lumaAlpha"
+  );
+
+  expect(Array.from(result.buffer)).toEqual([255, 128, 0, 0]);
+});
+
+it("fromBuffer - luma input expands to grey rgb", () => {
+  const src = Buffer.from([0, 100, 200, 255]);
+
+  const transformer = ImageTransformer.fromBuffer(src, 2, 2, "luma");
+  expect(transformer.getCurrentDimensions()).toEqual({ width: 2, height: 2 });
+
+  const result = transformer.toBufferSync("rgba");
+  expect(Array.from(result.buffer)).toEqual([
+    0, 0, 0, 255, 100, 100, 100, 255, 200, 200, 200, 255, 255, 255, 255, 255,
+  ]);
+});
+
+it("fromBuffer - lumaAlpha input", () => {
+  const src = Buffer.from([50, 0, 150, 255]);
+
+  const result = ImageTransformer.fromBuffer(
+    src,
+    2,
+    1,
+    "lumaAlpha"
+  ).toBufferSync("rgba");
+
+  expect(Array.from(result.buffer)).toEqual([
+    50, 50, 50, 0, 150, 150, 150, 255,
+  ]);
+});
+
+it("luma16 - little endian round trip", () => {
+  const values = [0x0000, 0x1234, 0x8080, 0xffff];
+  const src = Buffer.alloc(values.length * 2);
+  values.forEach((value, i) => src.writeUInt16LE(value, i * 2));
+
+  const transformer = ImageTransformer.fromBuffer(src, 2, 2, "luma16");
+
+  const result = transformer.toBufferSync("luma16");
+  expect(result.buffer.length).toBe(values.length * 2);
+  expect(Array.from(result.buffer)).toEqual(Array.from(src));
+
+  // Narrowing to 8 bits rounds to the nearest value
+  const narrowed = transformer.toBufferSync("luma");
+  expect(Array.from(narrowed.buffer)).toEqual([0, 18, 128, 255]);
+});
+
+it("lumaAlpha16 - little endian round trip", () => {
+  const values = [0x0102, 0xfffe, 0xa0b0, 0x0000];
+  const src = Buffer.alloc(values.length * 2);
+  values.forEach((value, i) => src.writeUInt16LE(value, i * 2));
+
+  const result = ImageTransformer.fromBuffer(
+    src,
+    2,
+    1,
+    "lumaAlpha16"
+  ).toBufferSync("lumaAlpha16");
+
+  expect(Array.from(result.buffer)).toEqual(Array.from(src));
+});
+
+it("luma - transforms work on greyscale images", () => {
+  const width = 8;
+  const height = 4;
+  const grey = ImageTransformer.fromBuffer(
+    generateQuadrantImage(width, height),
+    width,
+    height,
+    "rgba"
+  ).toBufferSync("luma");
+
+  const result = ImageTransformer.fromBuffer(grey.buffer, width, height, "luma")
+    .flipHorizontal()
+    .toBufferSync("luma");
+
+  for (let y = 0; y < height; y++) {
+    for (let x = 0; x < width; x++) {
+      expect(result.buffer[y * width + x]).toBe(
+        grey.buffer[y * width + (width - 1 - x)]
+      );
+    }
+  }
+});
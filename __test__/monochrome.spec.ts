@@ -0,0 +1,108 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+
+/** Build a luma image from rows of "#" (white) and "." (black) */
+function lumaFromRows(rows: string[]) {
+  const width = rows[0].length;
+  const height = rows.length;
+  const buffer = Buffer.from(
+    rows.flatMap((row) => [...row].map((c) => (c === "#" ? 255 : 0)))
+  );
+  return ImageTransformer.fromBuffer(buffer, width, height, "luma");
+}
+
+function countBits(buffer: Buffer) {
+  let count = 0;
+  for (const byte of buffer) {
+    for (let bit = 0; bit < 8; bit++) {
+      count += (byte >> bit) & 1;
+    }
+  }
+  return count;
+}
+
+it("toMonochromeSync - row major, msb first by default", () => {
+  const result = lumaFromRows(["#.........", "........##"]).toMonochromeSync();
+
+  expect(result.width).toBe(10);
+  expect(result.height).toBe(2);
+  // Each row is padded to a whole number of bytes
+  expect(Array.from(result.buffer)).toEqual([0x80, 0x00, 0x00, 0xc0]);
+});
+
+it("toMonochromeSync - row major, lsb first", () => {
+  const result = lumaFromRows(["#.........", "........##"]).toMonochromeSync({
+    bitOrder: "LsbFirst",
+  });
+
+  expect(Array.from(result.buffer)).toEqual([0x01, 0x00, 0x00, 0x03]);
+});
+
+it("toMonochromeSync - page major", () => {
+  const transformer = lumaFromRows([
+    "#.",
+    "..",
+    "..",
+    "..",
+    "..",
+    "..",
+    "..",
+    ".#",
+    "#.",
+    ".#",
+  ]);
+
+  // One byte per column for each band of 8 rows
+  const lsb = transformer.toMonochromeSync({
+    layout: "PageMajor",
+    bitOrder: "LsbFirst",
+  });
+  expect(Array.from(lsb.buffer)).toEqual([0x01, 0x80, 0x01, 0x02]);
+
+  const msb = transformer.toMonochromeSync({ layout: "PageMajor" });
+  expect(Array.from(msb.buffer)).toEqual([0x80, 0x01, 0x80, 0x40]);
+});
+
+it("toMonochrome - colour images are thresholded on luma", async () => {
+  // Yellow is bright and blue is dark, though both have a full channel
+  const src = Buffer.from([255, 255, 0, 0, 0, 255]);
+
+  const result = await ImageTransformer.fromBuffer(
+    src,
+    2,
+    1,
+    "rgb"
+  ).toMonochrome();
+
+  expect(Array.from(result.buffer)).toEqual([0x80]);
+});
+
+it("toMonochrome - dithering preserves the grey level", async () => {
+  const width = 32;
+  const height = 32;
+  // A quarter of the way from black to white
+  const transformer = ImageTransformer.fromBuffer(
+    Buffer.alloc(width * height, 64),
+    width,
+    height,
+    "luma"
+  );
+
+  const plain = await transformer.toMonochrome();
+  expect(countBits(plain.buffer)).toBe(0);
+
+  const ordered = await transformer.toMonochrome({ dither: "Ordered" });
+  expect(countBits(ordered.buffer)).toBe((width * height) / 4);
+
+  const floydSteinberg = await transformer.toMonochrome({
+    dither: "FloydSteinberg",
+  });
+  const coverage = countBits(floydSteinberg.buffer) / (width * height);
+  expect(Math.abs(coverage - 0.25)).toBeLessThan(0.02);
+
+  // Atkinson drops some of the error, so it only needs to be close
+  const atkinson = await transformer.toMonochrome({ dither: "Atkinson" });
+  const atkinsonCoverage = countBits(atkinson.buffer) / (width * height);
+  expect(atkinsonCoverage).toBeGreaterThan(0.15);
+  expect(atkinsonCoverage).toBeLessThan(0.3);
+});
@@ -0,0 +1,111 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+import { generateQuadrantImage } from "./test-utils.js";
+
+// Red, green, blue, white, black and a mid grey
+const colors = [
+  [255, 0, 0],
+  [0, 255, 0],
+  [0, 0, 255],
+  [255, 255, 255],
+  [0, 0, 0],
+  [128, 128, 128],
+];
+const source = Buffer.from(colors.flat());
+
+it("toBufferSync - rgb565 little endian", () => {
+  const result = ImageTransformer.fromBuffer(
+    source,
+    colors.length,
+    1,
+    "rgb"
+  ).toBufferSync("rgb565");
+
+  expect(result.buffer.length).toBe(colors.length * 2);
+  const values = colors.map((_, i) => result.buffer.readUInt16LE(i * 2));
+  expect(values).toEqual([0xf800, 0x07e0, 0x001f, 0xffff, 0x0000, 0x8410]);
+});
+
+it("toBufferSync - rgb565 big endian", () => {
+  const result = ImageTransformer.fromBuffer(
+    source,
+    colors.length,
+    1,
+    "rgb"
+  ).toBufferSync("rgb565be");
+
+  const values = colors.map((_, i) => result.buffer.readUInt16BE(i * 2));
+  expect(values).toEqual([0xf800, 0x07e0, 0x001f, 0xffff, 0x0000, 0x8410]);
+});
+
+it("toBufferSync - rgb555", () => {
+  const transformer = ImageTransformer.fromBuffer(
+    source,
+    colors.length,
+    1,
+    "rgb"
+  );
+  const expected = [0x7c00, 0x03e0, 0x001f, 0x7fff, 0x0000, 0x4210];
+
+  const little = transformer.toBufferSync("rgb555");
+  expect(colors.map((_, i) => little.buffer.readUInt16LE(i * 2))).toEqual(
+    expected
+  );
+
+  const big = transformer.toBufferSync("rgb555be");
+  expect(colors.map((_, i) => big.buffer.readUInt16BE(i * 2))).toEqual(
+    expected
+  );
+});
+
+it("fromBuffer - packed input round trips", () => {
+  const width = 8;
+  const height = 8;
+  const rgba = generateQuadrantImage(width, height);
+
+  for (const format of ["rgb565", "rgb565be", "rgb555", "rgb555be"] as const) {
+    const packed = ImageTransformer.fromBuffer(
+      rgba,
+      width,
+      height,
+      "rgba"
+    ).toBufferSync(format);
+
+    const result = ImageTransformer.fromBuffer(
+      packed.buffer,
+      width,
+      height,
+      format
+    ).toBufferSync("rgba");
+
+    // The quadrant colours are all at the ends of the range, so survive exactly
+    expect(Array.from(result.buffer)).toEqual(Array.from(rgba));
+  }
+});
+
+it("toBuffer - ordered dithering preserves the average colour", async () => {
+  const width = 16;
+  const height = 16;
+  // A level that falls between two rgb565 red values
+  const grey = Buffer.alloc(width * height * 3, 0);
+  for (let i = 0; i < width * height; i++) {
+    grey[i * 3] = 100;
+  }
+  const transformer = ImageTransformer.fromBuffer(grey, width, height, "rgb");
+
+  const redLevels = (buffer: Buffer) =>
+    Array.from({ length: width * height }, (_, i) =>
+      Math.round((((buffer.readUInt16LE(i * 2) >> 11) & 0x1f) * 255) / 31)
+    );
+
+  const plain = redLevels((await transformer.toBuffer("rgb565")).buffer);
+  expect(new Set(plain).size).toBe(1);
+
+  const dithered = redLevels(
+    (await transformer.toBuffer("rgb565", { dither: "Ordered" })).buffer
+  );
+  expect(new Set(dithered).size).toBe(2);
+
+  const average = dithered.reduce((sum, value) => sum + value, 0) / 256;
+  expect(Math.abs(average - 100)).toBeLessThan(1);
+});
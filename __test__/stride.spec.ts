@@ -0,0 +1,164 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+import { generateQuadrantImage } from "./test-utils.js";
+
+/** Copy packed rows into a buffer with the given stride, padding with 0xee */
+function padRows(
+  data: Buffer,
+  rowBytes: number,
+  rows: number,
+  stride: number
+) {
+  const padded = Buffer.alloc(stride * rows, 0xee);
+  for (let row = 0; row < rows; row++) {
+    data.copy(padded, row * stride, row * rowBytes, (row + 1) * rowBytes);
+  }
+  return padded;
+}
+
+it("fromBuffer - reads rows with a stride", () => {
+  const width = 6;
+  const height = 4;
+  const rgba = generateQuadrantImage(width, height);
+  const padded = padRows(rgba, width * 4, height, 32);
+
+  const result = ImageTransformer.fromBuffer(padded, width, height, "rgba", {
+    stride: 32,
+  }).toBufferSync("rgba");
+
+  expect(Array.from(result.buffer)).toEqual(Array.from(rgba));
+});
+
+it("fromBuffer - final row padding is optional", () => {
+  const width = 3;
+  const height = 2;
+  const rgb = Buffer.from([
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18,
+  ]);
+  const padded = padRows(rgb, width * 3, height, 12).subarray(0, 12 + 9);
+
+  const result = ImageTransformer.fromBuffer(padded, width, height, "rgb", {
+    stride: 12,
+  }).toBufferSync("rgb");
+
+  expect(Array.from(result.buffer)).toEqual(Array.from(rgb));
+});
+
+it("fromBuffer - stride smaller than a row", () => {
+  expect(() =>
+    ImageTransformer.fromBuffer(Buffer.alloc(64), 4, 4, "rgba", { stride: 8 })
+  ).toThrow("Stride is smaller than a row of pixels");
+});
+
+it("toBufferSync - writes rows with a stride", () => {
+  const width = 5;
+  const height = 3;
+  const transformer = ImageTransformer.fromBuffer(
+    generateQuadrantImage(width, height),
+    width,
+    height,
+    "rgba"
+  );
+  const tight = transformer.toBufferSync("rgb");
+
+  const result = transformer.toBufferSync("rgb", { stride: 20 });
+  expect(result.buffer.length).toBe(20 * height);
+
+  for (let row = 0; row < height; row++) {
+    const start = row * 20;
+    expect(Array.from(result.buffer.subarray(start, start + 15))).toEqual(
+      Array.from(tight.buffer.subarray(row * 15, (row + 1) * 15))
+    );
+    // Padding is zeroed
+    expect(Array.from(result.buffer.subarray(start + 15, start + 20))).toEqual([
+      0, 0, 0, 0, 0,
+    ]);
+  }
+});
+
+it("toBuffer - row alignment", async () => {
+  const width = 5;
+  const height = 2;
+  const transformer = ImageTransformer.fromBuffer(
+    generateQuadrantImage(width, height),
+    width,
+    height,
+    "rgba"
+  );
+
+  // 15 byte rows round up to 16
+  const result = await transformer.toBuffer("rgb", { rowAlignment: 4 });
+  expect(result.buffer.length).toBe(16 * height);
+
+  // Rows that are already aligned are left alone
+  const aligned = await transformer.toBuffer("rgba", { rowAlignment: 4 });
+  expect(aligned.buffer.length).toBe(20 * height);
+});
+
+it("toBufferSync - invalid stride options", () => {
+  const transformer = ImageTransformer.fromBuffer(
+    Buffer.alloc(16),
+    2,
+    2,
+    "rgba"
+  );
+
+  expect(() => transformer.toBufferSync("rgba", { stride: 4 })).toThrow(
+    "Stride is smaller than a row of pixels"
+  );
+  expect(() =>
+    transformer.toBufferSync("rgba", { stride: 16, rowAlignment: 16 })
+  ).toThrow("Only one of stride and rowAlignment can be set");
+});
+
+it("toBufferSync - oversized stride throws instead of aborting", async () => {
+  // 1000 rows 4GB apart is far more memory than can be allocated
+  const transformer = ImageTransformer.fromBuffer(
+    Buffer.alloc(4 * 1000),
+    1,
+    1000,
+    "rgba"
+  );
+
+  expect(() => transformer.toBufferSync("rgba", { stride: 4e9 })).toThrow(
+    "Stride is too large"
+  );
+  await expect(transformer.toBuffer("rgba", { stride: 4e9 })).rejects.toThrow(
+    "Stride is too large"
+  );
+  expect(() =>
+    transformer.toBufferIntoSync(Buffer.alloc(16), "rgba", 0, { stride: 4e9 })
+  ).toThrow("Target buffer is too small");
+});
+
+it("i420 - stride round trip", () => {
+  const width = 6;
+  const height = 4;
+  const transformer = ImageTransformer.fromBuffer(
+    generateQuadrantImage(width, height),
+    width,
+    height,
+    "rgba"
+  );
+  const tight = transformer.toBufferSync("i420");
+
+  // The chroma planes use half the luma stride
+  const padded = transformer.toBufferSync("i420", { stride: 8 });
+  expect(padded.buffer.length).toBe(8 * 4 + 4 * 2 * 2);
+
+  // Reading either buffer gives the same image
+  const fromPadded = ImageTransformer.fromBuffer(
+    padded.buffer,
+    width,
+    height,
+    "i420",
+    { stride: 8 }
+  ).toBufferSync("rgb");
+  const fromTight = ImageTransformer.fromBuffer(
+    tight.buffer,
+    width,
+    height,
+    "i420"
+  ).toBufferSync("rgb");
+  expect(Array.from(fromPadded.buffer)).toEqual(Array.from(fromTight.buffer));
+});
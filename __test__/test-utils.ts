@@ -2,6 +2,8 @@
  * Test utilities for image testing
  */
 
+import zlib from "zlib";
+
 /**
  * Generate a solid color image buffer
  */
@@ -264,3 +266,244 @@ export function createTestPattern(
 
   return Buffer.from(buffer);
 }
+
+/**
+ * Create a test image of small saturated blocks with varying alpha. The hard
+ * edges make resize filters with negative lobes overshoot.
+ */
+export function createHardEdgePattern(
+  width: number,
+  height: number,
+  format: "rgba" | "rgb" = "rgba"
+): Buffer {
+  const channels = format === "rgba" ? 4 : 3;
+  const buffer = new Uint8Array(width * height * channels);
+  const colors = [
+    [255, 0, 0],
+    [0, 255, 0],
+    [0, 0, 255],
+    [255, 255, 255],
+    [0, 0, 0],
+    [255, 255, 0],
+  ];
+  const alphas = [255, 0, 128, 255, 64];
+
+  for (let y = 0; y < height; y++) {
+    for (let x = 0; x < width; x++) {
+      const offset = (y * width + x) * channels;
+      const block = Math.floor(x / 5) + Math.floor(y / 7);
+
+      buffer.set(colors[block % colors.length], offset);
+      if (format === "rgba") {
+        buffer[offset + 3] = alphas[block % alphas.length];
+      }
+    }
+  }
+
+  return Buffer.from(buffer);
+}
+
+/**
+ * Encode an 8-bit RGBA buffer as a 16-bit RGBA PNG.
+ *
+ * 16-bit images are resized by image-rs itself rather than the SIMD resizer, which makes
+ * this useful for comparing the two implementations.
+ */
+export function encodeRgba16Png(
+  width: number,
+  height: number,
+  rgba: Buffer
+): Buffer {
+  const rowLength = 1 + width * 8;
+  const raw = Buffer.alloc(height * rowLength);
+
+  for (let y = 0; y < height; y++) {
+    // Filter type 0 (none)
+    raw[y * rowLength] = 0;
+    for (let i = 0; i < width * 4; i++) {
+      const value = rgba[y * width * 4 + i] * 257;
+      raw.writeUInt16BE(value, y * rowLength + 1 + i * 2);
+    }
+  }
+
+  const chunk = (type: string, data: Buffer) => {
+    const length = Buffer.alloc(4);
+    length.writeUInt32BE(data.length);
+    const body = Buffer.concat([Buffer.from(type, "ascii"), data]);
+    const crc = Buffer.alloc(4);
+    crc.writeUInt32BE(zlib.crc32(body));
+    return Buffer.concat([length, body, crc]);
+  };
+
+  const header = Buffer.alloc(13);
+  header.writeUInt32BE(width, 0);
+  header.writeUInt32BE(height, 4);
+  header[8] = 16; // Bit depth
+  header[9] = 6; // Colour type: RGBA
+
+  return Buffer.concat([
+    Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]),
+    chunk("IHDR", header),
+    chunk("IDAT", zlib.deflateSync(raw)),
+    chunk("IEND", Buffer.alloc(0)),
+  ]);
+}
+
+/**
+ * Insert an EXIF segment containing only an orientation tag into a JPEG
+ */
+export function withExifOrientation(jpeg: Buffer, orientation: number): Buffer {
+  // Big endian TIFF header, followed by a single IFD with one entry
+  const tiff = Buffer.alloc(26);
+  tiff.write("MM", 0, "ascii");
+  tiff.writeUInt16BE(42, 2);
+  tiff.writeUInt32BE(8, 4); // Offset of the first IFD
+  tiff.writeUInt16BE(1, 8); // Number of entries
+  tiff.writeUInt16BE(0x0112, 10); // Orientation tag
+  tiff.writeUInt16BE(3, 12); // Type: SHORT
+  tiff.writeUInt32BE(1, 14); // Count
+  tiff.writeUInt16BE(orientation, 18);
+  tiff.writeUInt32BE(0, 22); // No next IFD
+
+  const payload = Buffer.concat([Buffer.from("Exif\0\0", "binary"), tiff]);
+  const header = Buffer.from([0xff, 0xe1, 0, 0]);
+  header.writeUInt16BE(payload.length + 2, 2);
+
+  // APP1 goes straight after the SOI marker
+  return Buffer.concat([jpeg.subarray(0, 2), header, payload, jpeg.subarray(2)]);
+}
+
+/**
+ * Encode an 8-bit RGB buffer in one of the simple uncompressed formats, for decoding tests.
+ *
+ * ICO files wrap a PNG, which needs to be provided by the caller.
+ */
+export function encodeUncompressedImage(
+  format: "pnm" | "tga" | "bmp" | "qoi" | "tiff",
+  width: number,
+  height: number,
+  rgb: Buffer
+): Buffer {
+  switch (format) {
+    case "pnm":
+      return Buffer.concat([
+        Buffer.from(`P6\n${width} ${height}\n255\n`, "ascii"),
+        rgb,
+      ]);
+    case "tga": {
+      const header = Buffer.alloc(18);
+      header[2] = 2; // Uncompressed true-colour
+      header.writeUInt16LE(width, 12);
+      header.writeUInt16LE(height, 14);
+      header[16] = 24; // Bits per pixel
+      header[17] = 0x20; // Top-left origin
+
+      const pixels = Buffer.alloc(width * height * 3);
+      for (let i = 0; i < width * height; i++) {
+        pixels[i * 3] = rgb[i * 3 + 2];
+        pixels[i * 3 + 1] = rgb[i * 3 + 1];
+        pixels[i * 3 + 2] = rgb[i * 3];
+      }
+      return Buffer.concat([header, pixels]);
+    }
+    case "bmp": {
+      // Rows are bottom-up and padded to 4 bytes
+      const rowLength = Math.ceil((width * 3) / 4) * 4;
+      const pixels = Buffer.alloc(rowLength * height);
+      for (let y = 0; y < height; y++) {
+        const row = (height - 1 - y) * rowLength;
+        for (let x = 0; x < width; x++) {
+          const i = (y * width + x) * 3;
+          pixels[row + x * 3] = rgb[i + 2];
+          pixels[row + x * 3 + 1] = rgb[i + 1];
+          pixels[row + x * 3 + 2] = rgb[i];
+        }
+      }
+
+      const header = Buffer.alloc(54);
+      header.write("BM", 0, "ascii");
+      header.writeUInt32LE(54 + pixels.length, 2);
+      header.writeUInt32LE(54, 10); // Pixel data offset
+      header.writeUInt32LE(40, 14); // BITMAPINFOHEADER
+      header.writeInt32LE(width, 18);
+      header.writeInt32LE(height, 22);
+      header.writeUInt16LE(1, 26); // Planes
+      header.writeUInt16LE(24, 28); // Bits per pixel
+      header.writeUInt32LE(pixels.length, 34);
+      return Buffer.concat([header, pixels]);
+    }
+    case "qoi": {
+      const header = Buffer.alloc(14);
+      header.write("qoif", 0, "ascii");
+      header.writeUInt32BE(width, 4);
+      header.writeUInt32BE(height, 8);
+      header[12] = 3; // Channels
+      header[13] = 0; // sRGB
+
+      // Every pixel as a full QOI_OP_RGB chunk
+      const chunks = Buffer.alloc(width * height * 4);
+      for (let i = 0; i < width * height; i++) {
+        chunks[i * 4] = 0xfe;
+        rgb.copy(chunks, i * 4 + 1, i * 3, i * 3 + 3);
+      }
+      const end = Buffer.from([0, 0, 0, 0, 0, 0, 0, 1]);
+      return Buffer.concat([header, chunks, end]);
+    }
+    case "tiff": {
+      // Little endian, a single uncompressed strip
+      const entries: [number, number, number][] = [
+        [256, 3, width], // ImageWidth
+        [257, 3, height], // ImageLength
+        [258, 3, 8], // BitsPerSample (applies to every sample)
+        [259, 3, 1], // Compression: none
+        [262, 3, 2], // PhotometricInterpretation: RGB
+        [273, 4, 0], // StripOffsets, filled in below
+        [277, 3, 3], // SamplesPerPixel
+        [278, 3, height], // RowsPerStrip
+        [279, 4, rgb.length], // StripByteCounts
+      ];
+      const ifdLength = 2 + entries.length * 12 + 4;
+      const dataOffset = 8 + ifdLength;
+
+      const header = Buffer.alloc(dataOffset);
+      header.write("II", 0, "ascii");
+      header.writeUInt16LE(42, 2);
+      header.writeUInt32LE(8, 4); // Offset of the first IFD
+      header.writeUInt16LE(entries.length, 8);
+      entries.forEach(([tag, type, value], i) => {
+        const offset = 10 + i * 12;
+        header.writeUInt16LE(tag, offset);
+        header.writeUInt16LE(type, offset + 2);
+        header.writeUInt32LE(1, offset + 4); // Count
+        const data = tag === 273 ? dataOffset : value;
+        if (type === 3) {
+          header.writeUInt16LE(data, offset + 8);
+        } else {
+          header.writeUInt32LE(data, offset + 8);
+        }
+      });
+      return Buffer.concat([header, rgb]);
+    }
+  }
+}
+
+/**
+ * Wrap a PNG in a single image ICO file
+ */
+export function wrapPngInIco(
+  png: Buffer,
+  width: number,
+  height: number
+): Buffer {
+  const header = Buffer.alloc(22);
+  header.writeUInt16LE(0, 0); // Reserved
+  header.writeUInt16LE(1, 2); // Type: icon
+  header.writeUInt16LE(1, 4); // Number of images
+  header[6] = width % 256; // 0 means 256
+  header[7] = height % 256;
+  header.writeUInt16LE(1, 10); // Colour planes
+  header.writeUInt16LE(32, 12); // Bits per pixel
+  header.writeUInt32LE(png.length, 14);
+  header.writeUInt32LE(22, 18); // Image data offset
+  return Buffer.concat([header, png]);
+}
@@ -0,0 +1,267 @@
+import { describe, it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+import { generateSolidColorImage } from "./test-utils.js";
+import fs from "fs";
+import path from "path";
+
+const font = fs.readFileSync(
+  path.join(import.meta.dirname, "fixtures", "DejaVuSansMono.ttf")
+);
+
+const white = { red: 255, green: 255, blue: 255, alpha: 255 };
+
+function findInkBounds(
+  pixels: Buffer,
+  width: number,
+  height: number
+): { left: number; right: number; top: number; bottom: number } | null {
+  let bounds: { left: number; right: number; top: number; bottom: number } | null =
+    null;
+
+  for (let y = 0; y < height; y++) {
+    for (let x = 0; x < width; x++) {
+      // Any non-black pixel on a black background is ink
+      const offset = (y * width + x) * 4;
+      if (pixels[offset] > 0) {
+        if (!bounds) {
+          bounds = { left: x, right: x, top: y, bottom: y };
+        } else {
+          bounds.left = Math.min(bounds.left, x);
+          bounds.right = Math.max(bounds.right, x);
+          bounds.top = Math.min(bounds.top, y);
+          bounds.bottom = Math.max(bounds.bottom, y);
+        }
+      }
+    }
+  }
+
+  return bounds;
+}
+
+describe("drawText", () => {
+  it("should draw text without changing dimensions", () => {
+    const size = { width: 64, height: 32 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    const transformer = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    ).drawText(font, "Hi", { size: 16, color: white });
+
+    expect(transformer.getCurrentDimensions()).toEqual(size);
+
+    const result = transformer.toBufferSync("rgba");
+    expect(result.width).toBe(size.width);
+    expect(result.height).toBe(size.height);
+
+    const bounds = findInkBounds(result.buffer, size.width, size.height);
+    expect(bounds).not.toBeNull();
+    // Top-left aligned by default
+    expect(bounds!.left).toBeLessThan(8);
+    expect(bounds!.top).toBeLessThan(8);
+  });
+
+  it("should anti-alias glyph edges", () => {
+    const size = { width: 32, height: 32 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    const result = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    )
+      .drawText(font, "O", { size: 24, color: white })
+      .toBufferSync("rgba");
+
+    const values = new Set<number>();
+    for (let i = 0; i < result.buffer.length; i += 4) {
+      values.add(result.buffer[i]);
+    }
+
+    // Partial coverage produces intermediate values, not just 0 and 255
+    expect(values.size).toBeGreaterThan(2);
+  });
+
+  it("should blend using the text alpha", () => {
+    const size = { width: 32, height: 32 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    const result = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    )
+      .drawText(font, "█", {
+        size: 32,
+        color: { red: 255, green: 255, blue: 255, alpha: 128 },
+      })
+      .toBufferSync("rgba");
+
+    let max = 0;
+    for (let i = 0; i < result.buffer.length; i += 4) {
+      max = Math.max(max, result.buffer[i]);
+      // Background stays opaque
+      expect(result.buffer[i + 3]).toBe(255);
+    }
+
+    expect(max).toBeGreaterThan(120);
+    expect(max).toBeLessThan(136);
+  });
+
+  it("should align text within the box", () => {
+    const size = { width: 80, height: 40 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    const render = (
+      align: "Left" | "Center" | "Right",
+      verticalAlign: "Top" | "Center" | "Bottom"
+    ) => {
+      const result = ImageTransformer.fromBuffer(
+        buffer,
+        size.width,
+        size.height,
+        "rgba"
+      )
+        .drawText(font, "ab", { size: 12, color: white, align, verticalAlign })
+        .toBufferSync("rgba");
+      return findInkBounds(result.buffer, size.width, size.height)!;
+    };
+
+    const topLeft = render("Left", "Top");
+    const center = render("Center", "Center");
+    const bottomRight = render("Right", "Bottom");
+
+    expect(center.left).toBeGreaterThan(topLeft.left);
+    expect(bottomRight.left).toBeGreaterThan(center.left);
+    expect(center.top).toBeGreaterThan(topLeft.top);
+    expect(bottomRight.top).toBeGreaterThan(center.top);
+
+    expect(bottomRight.right).toBeLessThan(size.width);
+    expect(bottomRight.bottom).toBeLessThan(size.height);
+  });
+
+  it("should wrap long lines and honour newlines", () => {
+    const size = { width: 48, height: 64 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    const draw = (text: string, wrap: boolean) => {
+      const result = ImageTransformer.fromBuffer(
+        buffer,
+        size.width,
+        size.height,
+        "rgba"
+      )
+        .drawText(font, text, { size: 12, color: white, wrap })
+        .toBufferSync("rgba");
+      return findInkBounds(result.buffer, size.width, size.height)!;
+    };
+
+    const single = draw("one", false);
+    const wrapped = draw("one two three", true);
+    const newlines = draw("one\ntwo\nthree", false);
+
+    expect(wrapped.bottom).toBeGreaterThan(single.bottom);
+    expect(wrapped.right).toBeLessThan(size.width);
+    expect(newlines.bottom).toBeGreaterThan(single.bottom);
+  });
+
+  it("should clip text to the box", () => {
+    const size = { width: 64, height: 32 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    const result = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    )
+      .drawText(font, "clipped text", {
+        size: 16,
+        color: white,
+        x: 10,
+        y: 4,
+        width: 20,
+        height: 20,
+      })
+      .toBufferSync("rgba");
+
+    const bounds = findInkBounds(result.buffer, size.width, size.height)!;
+    expect(bounds.left).toBeGreaterThanOrEqual(10);
+    expect(bounds.right).toBeLessThan(30);
+    expect(bounds.top).toBeGreaterThanOrEqual(4);
+    expect(bounds.bottom).toBeLessThan(24);
+  });
+
+  it("should draw the visible part of glyphs larger than the image", () => {
+    const size = { width: 16, height: 16 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    // A full block glyph, positioned so that it covers the whole image
+    const result = ImageTransformer.fromBuffer(
+      buffer,
+      size.width,
+      size.height,
+      "rgba"
+    )
+      .drawText(font, "\u2588\u2588\u2588", {
+        size: 2000,
+        color: white,
+        x: -100,
+        y: -100,
+      })
+      .toBufferSync("rgba");
+
+    for (let i = 0; i < result.buffer.length; i += 4) {
+      expect(result.buffer[i]).toBe(255);
+    }
+  });
+
+  it("should ignore text boxes at extreme coordinates", () => {
+    const size = { width: 16, height: 16 };
+    const buffer = generateSolidColorImage(size.width, size.height, 0, 0, 0);
+
+    for (const position of [
+      { x: -Number.MAX_VALUE, y: -Number.MAX_VALUE },
+      { x: Number.MAX_VALUE, y: Number.MAX_VALUE, width: 100, height: 100 },
+    ]) {
+      const result = ImageTransformer.fromBuffer(
+        buffer,
+        size.width,
+        size.height,
+        "rgba"
+      )
+        .drawText(font, "Hi", { size: 12, color: white, ...position })
+        .toBufferSync("rgba");
+
+      expect(findInkBounds(result.buffer, size.width, size.height)).toBeNull();
+    }
+  });
+
+  it("should reject invalid fonts and sizes", () => {
+    const transformer = ImageTransformer.fromBuffer(
+      generateSolidColorImage(8, 8, 0, 0, 0),
+      8,
+      8,
+      "rgba"
+    );
+
+    expect(() => {
+      transformer.drawText(Buffer.from([1, 2, 3, 4]), "x", {
+        size: 12,
+        color: white,
+      });
+    }).toThrow();
+
+    expect(() => {
+      transformer.drawText(font, "x", { size: 0, color: white });
+    }).toThrow();
+
+    expect(() => {
+      transformer.drawText(font, "x", { size: 1e30, color: white });
+    }).toThrow("Font size must be at most 4096");
+  });
+});
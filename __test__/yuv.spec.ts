@@ -0,0 +1,192 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+
+/** Assert each rgb pixel is within a couple of levels of the expected colour */
+function expectPixels(buffer: Buffer, expected: number[][]) {
+  expect(buffer.length).toBe(expected.length * 3);
+  expected.forEach((pixel, i) => {
+    pixel.forEach((value, channel) => {
+      expect(Math.abs(buffer[i * 3 + channel] - value)).toBeLessThanOrEqual(2);
+    });
+  });
+}
+
+const white = [255, 255, 255];
+const black = [0, 0, 0];
+const red = [255, 0, 0];
+
+it("fromBuffer - nv12 with bt601 limited range by default", () => {
+  // Each 2x2 block shares one interleaved U and V pair
+  const src = Buffer.from([
+    // Y plane
+    235, 235, 81, 81, 235, 235, 81, 81,
+    // UV plane
+    128, 128, 90, 240,
+  ]);
+
+  const result = ImageTransformer.fromBuffer(src, 4, 2, "nv12").toBufferSync(
+    "rgb"
+  );
+
+  expectPixels(result.buffer, [white, white, red, red, white, white, red, red]);
+});
+
+it("fromBuffer - i420", () => {
+  const src = Buffer.from([
+    // Y plane
+    235, 235, 81, 81, 235, 235, 81, 81,
+    // U plane
+    128, 90,
+    // V plane
+    128, 240,
+  ]);
+
+  const result = ImageTransformer.fromBuffer(src, 4, 2, "i420").toBufferSync(
+    "rgb"
+  );
+
+  expectPixels(result.buffer, [white, white, red, red, white, white, red, red]);
+});
+
+it("fromBuffer - i420 with odd dimensions", () => {
+  // Chroma planes are rounded up to 2x2, for a 3x3 image
+  const src = Buffer.from([
+    16, 16, 16, 16, 16, 16, 16, 16, 16, 128, 128, 128, 128, 128, 128, 128, 128,
+  ]);
+
+  const result = ImageTransformer.fromBuffer(src, 3, 3, "i420").toBufferSync(
+    "rgb"
+  );
+
+  expectPixels(result.buffer, Array(9).fill(black));
+});
+
+it("fromBuffer - yuyv and uyvy", () => {
+  const yuyv = Buffer.from([235, 128, 16, 128, 81, 90, 81, 240]);
+  const uyvy = Buffer.from([128, 235, 128, 16, 90, 81, 240, 81]);
+
+  for (const [src, format] of [[yuyv, "yuyv"], [uyvy, "uyvy"]] as const) {
+    const result = ImageTransformer.fromBuffer(src, 2, 2, format).toBufferSync(
+      "rgb"
+    );
+    expectPixels(result.buffer, [white, black, red, red]);
+  }
+});
+
+it("fromBuffer - yuv matrix and range options", () => {
+  // Pure red, as coded by bt709 in limited range
+  const bt709 = Buffer.from([63, 102, 63, 240]);
+  const result = ImageTransformer.fromBuffer(bt709, 2, 1, "yuyv", {
+    yuvMatrix: "Bt709",
+  }).toBufferSync("rgb");
+  expectPixels(result.buffer, [red, red]);
+
+  // The same values read as bt601 give a different colour
+  const wrongMatrix = ImageTransformer.fromBuffer(
+    bt709,
+    2,
+    1,
+    "yuyv"
+  ).toBufferSync("rgb");
+  expect(wrongMatrix.buffer[0]).toBeLessThan(240);
+
+  // Pure red, as coded by bt601 in full range
+  const full = Buffer.from([76, 85, 76, 255]);
+  const fullResult = ImageTransformer.fromBuffer(full, 2, 1, "yuyv", {
+    yuvRange: "Full",
+  }).toBufferSync("rgb");
+  expectPixels(fullResult.buffer, [red, red]);
+});
+
+it("fromBuffer - yuv buffer too small", () => {
+  const transformer = ImageTransformer.fromBuffer(
+    Buffer.alloc(4 * 4),
+    4,
+    4,
+    "nv12"
+  );
+
+  expect(() => transformer.toBufferSync("rgb")).toThrow("Invalid pixel buffer");
+});
+
+it("toBufferSync - yuv output round trips", () => {
+  const width = 6;
+  const height = 4;
+  // Each 2x2 block is a single colour, so the chroma survives subsampling
+  const colors = [
+    [255, 0, 0],
+    [0, 255, 0],
+    [0, 0, 255],
+    [255, 255, 255],
+    [0, 0, 0],
+    [128, 64, 32],
+  ];
+  const rgb = Buffer.alloc(width * height * 3);
+  for (let y = 0; y < height; y++) {
+    for (let x = 0; x < width; x++) {
+      const color = colors[Math.floor(y / 2) * 3 + Math.floor(x / 2)];
+      rgb.set(color, (y * width + x) * 3);
+    }
+  }
+
+  for (const format of ["nv12", "i420", "yuyv", "uyvy"] as const) {
+    for (const options of [
+      {},
+      { yuvMatrix: "Bt709", yuvRange: "Full" },
+    ] as const) {
+      const yuv = ImageTransformer.fromBuffer(
+        rgb,
+        width,
+        height,
+        "rgb"
+      ).toBufferSync(format, options);
+
+      const packed = format === "yuyv" || format === "uyvy";
+      expect(yuv.buffer.length).toBe(
+        packed ? width * height * 2 : (width * height * 3) / 2
+      );
+
+      const result = ImageTransformer.fromBuffer(
+        yuv.buffer,
+        width,
+        height,
+        format,
+        options
+      ).toBufferSync("rgb");
+      expectPixels(
+        result.buffer,
+        Array.from({ length: width * height }, (_, i) =>
+          Array.from(rgb.subarray(i * 3, i * 3 + 3))
+        )
+      );
+    }
+  }
+});
+
+it("toBufferSync - uyvy byte order", () => {
+  const src = Buffer.from([255, 255, 255, 0, 0, 0]);
+
+  const result = ImageTransformer.fromBuffer(src, 2, 1, "rgb").toBufferSync(
+    "uyvy"
+  );
+
+  // White and black share grey chroma
+  expect(Array.from(result.buffer)).toEqual([128, 235, 128, 16]);
+});
+
+it("toBufferSync - yuv chroma is averaged", () => {
+  // Red and blue side by side share one chroma sample
+  const src = Buffer.from([255, 0, 0, 0, 0, 255]);
+
+  const result = ImageTransformer.fromBuffer(src, 2, 1, "rgb").toBufferSync(
+    "yuyv",
+    { yuvRange: "Full" }
+  );
+
+  const [y0, u, y1, v] = result.buffer;
+  expect(y0).toBe(76);
+  expect(y1).toBe(29);
+  // Red alone would give 85 and 255, and blue alone 255 and 107
+  expect(u).toBe(170);
+  expect(v).toBe(181);
+});
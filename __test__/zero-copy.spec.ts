@@ -0,0 +1,131 @@
+import { it, expect } from "vitest";
+import { ImageTransformer } from "../index.js";
+import { generateQuadrantImage } from "./test-utils.js";
+
+it("fromBuffer - copies the buffer by default", () => {
+  const src = Buffer.from([1, 2, 3, 4]);
+  const transformer = ImageTransformer.fromBuffer(src, 2, 2, "luma");
+
+  src.fill(0);
+
+  expect(Array.from(transformer.toBufferSync("luma").buffer)).toEqual([
+    1, 2, 3, 4,
+  ]);
+});
+
+it("fromBuffer - zeroCopy reads the given buffer", async () => {
+  const src = Buffer.from([1, 2, 3, 4]);
+  const transformer = ImageTransformer.fromBuffer(src, 2, 2, "luma", {
+    zeroCopy: true,
+  });
+
+  expect(Array.from(transformer.toBufferSync("luma").buffer)).toEqual([
+    1, 2, 3, 4,
+  ]);
+
+  // Changes made between renders are picked up
+  src[0] = 100;
+  const result = await transformer.toBuffer("luma");
+  expect(Array.from(result.buffer)).toEqual([100, 2, 3, 4]);
+});
+
+it("fromBuffer - zeroCopy with a view into a larger buffer", async () => {
+  const backing = new Uint8Array(32).fill(0xff);
+  backing.set([1, 2, 3, 4], 8);
+
+  const transformer = ImageTransformer.fromBuffer(
+    backing.subarray(8, 12),
+    2,
+    2,
+    "luma",
+    { zeroCopy: true }
+  ).flipHorizontal();
+
+  const result = await transformer.toBuffer("luma");
+  expect(Array.from(result.buffer)).toEqual([2, 1, 4, 3]);
+});
+
+it("fromBuffer - accepts an ArrayBuffer", async () => {
+  const src = new Uint8Array([1, 2, 3, 4]);
+
+  const copied = ImageTransformer.fromBuffer(src.buffer, 2, 2, "luma");
+  const borrowed = ImageTransformer.fromBuffer(src.buffer, 2, 2, "luma", {
+    zeroCopy: true,
+  });
+
+  src[0] = 100;
+
+  expect(Array.from(copied.toBufferSync("luma").buffer)).toEqual([
+    1, 2, 3, 4,
+  ]);
+  const result = await borrowed.toBuffer("luma");
+  expect(Array.from(result.buffer)).toEqual([100, 2, 3, 4]);
+});
+
+it("fromEncodedImage - zeroCopy", async () => {
+  const width = 8;
+  const height = 8;
+  const rgba = generateQuadrantImage(width, height);
+  const png = ImageTransformer.fromBuffer(
+    rgba,
+    width,
+    height,
+    "rgba"
+  ).toEncodedImageSync("png").buffer;
+
+  const transformer = ImageTransformer.fromEncodedImage(png, {
+    zeroCopy: true,
+  });
+  expect(transformer.getCurrentDimensions()).toEqual({ width, height });
+
+  const result = await transformer.toBuffer("rgba");
+  expect(Array.from(result.buffer)).toEqual(Array.from(rgba));
+});
+
+it("overlay - zeroCopy source rendered on a worker", async () => {
+  const base = ImageTransformer.fromBuffer(
+    Buffer.alloc(4 * 4 * 4),
+    4,
+    4,
+    "rgba",
+    { zeroCopy: true }
+  );
+  const dot = ImageTransformer.fromBuffer(
+    Buffer.from([255, 0, 0, 255]),
+    1,
+    1,
+    "rgba",
+    { zeroCopy: true }
+  );
+
+  const result = await base.overlay(dot, 1, 2).toBuffer("rgba");
+
+  const offset = (2 * 4 + 1) * 4;
+  expect(Array.from(result.buffer.subarray(offset, offset + 4))).toEqual([
+    255, 0, 0, 255,
+  ]);
+  expect(result.buffer[0]).toBe(0);
+});
+
+it("fromEncodedImage - zeroCopy from an ArrayBuffer", async () => {
+  const width = 8;
+  const height = 8;
+  const rgba = generateQuadrantImage(width, height);
+  const png = ImageTransformer.fromBuffer(
+    rgba,
+    width,
+    height,
+    "rgba"
+  ).toEncodedImageSync("png").buffer;
+
+  // A copy, as a Buffer may be a view into a larger shared pool
+  const arrayBuffer = new Uint8Array(png).buffer;
+
+  const transformer = ImageTransformer.fromEncodedImage(arrayBuffer, {
+    zeroCopy: true,
+  });
+  expect(transformer.getSourceFormat()).toBe("png");
+
+  const result = await transformer.toBuffer("rgba");
+  expect(Array.from(result.buffer)).toEqual(Array.from(rgba));
+});
@@ -2,38 +2,43 @@
 /* eslint-disable */
 export declare class ImageTransformer {
   /**
-   * Create an `ImageTransformer` from a `Buffer` or `Uint8Array`
+   * Create an `ImageTransformer` from a `Buffer`, `Uint8Array` or `ArrayBuffer`
    *
    * @param buffer - The image to transform
    * @param width - Width of the image
    * @param height - Height of the image
    * @param format - Pixel format of the buffer
+   * @param options - Optional settings for reading the buffer
    */
-  static fromBuffer(buffer: Uint8Array, width: number, height: number, format: PixelFormat): ImageTransformer
+  static fromBuffer(buffer: Uint8Array | ArrayBuffer, width: number, height: number, format: PixelFormat, options?: RawImageOptions | undefined | null): ImageTransformer
   /**
-   * Create an `ImageTransformer` from a `Buffer` or `Uint8Array` containing an encoded image
+   * Create an `ImageTransformer` from a `Buffer`, `Uint8Array` or `ArrayBuffer` containing an
+   * encoded image
    *
    * @param image - The encoded image to decode
+   * @param options - Optional decoding options
    * @returns An `ImageTransformer` instance
    * This method does not require width or height, as it will be determined from reading the image
    */
-  static fromEncodedImage(image: Uint8Array): ImageTransformer
+  static fromEncodedImage(image: Uint8Array | ArrayBuffer, options?: DecodeOptions | undefined | null): ImageTransformer
   /**
    * Create an `ImageTransformer` from a data URL string (e.g., "data:image/png;base64,...")
    *
    * @param data_url - The data URL string containing the encoded image
+   * @param options - Optional decoding options
    * @returns An `ImageTransformer` instance
    * This method parses the data URL, extracts the base64 data, and decodes the image
    */
-  static fromImageDataUrl(dataUrl: string): ImageTransformer
+  static fromImageDataUrl(dataUrl: string, options?: DecodeOptions | undefined | null): ImageTransformer
   /**
    * Add a scale step to the transform sequence
    *
    * @param width - Target width for the image
    * @param height - Target height for the image
    * @param mode - Method to use when source and target aspect ratios do not match
+   * @param options - Optional scaling options
    */
-  scale(width: number, height: number, mode?: ResizeMode | undefined | null): this
+  scale(width: number, height: number, mode?: ResizeMode | undefined | null, options?: ScaleOptions | undefined | null): this
   /**
    * Add a crop step to the transform sequence
    *
@@ -48,8 +53,19 @@ export declare class ImageTransformer {
    *
    * @param width - Target width for the image
    * @param height - Target height for the image
+   * @param gravity - Which part of the image to keep. Defaults to the center
    */
-  cropCenter(width: number, height: number): this
+  cropCenter(width: number, height: number, gravity?: Gravity | undefined | null): this
+  /**
+   * Add a content aware crop step to the transform sequence
+   *
+   * The crop window is chosen to keep the most interesting part of the image
+   *
+   * @param width - Target width for the image
+   * @param height - Target height for the image
+   * @param strategy - How to score the interest of a region. Defaults to `Attention`
+   */
+  smartCrop(width: number, height: number, strategy?: SmartCropStrategy | undefined | null): this
   /**
    * Pad the image by the specified amount
    *
@@ -70,14 +86,41 @@ export declare class ImageTransformer {
    * @param rotation - The amount to rotate by
    */
   rotate(rotation: RotationMode): this
+  /**
+   * Add a rotation by an arbitrary angle to the transform sequence
+   *
+   * @param angle - The angle in degrees to rotate clockwise by
+   * @param options - Optional rotation options
+   */
+  rotateDegrees(angle: number, options?: RotateOptions | undefined | null): this
   /**
    * Overlay another image on top of the current image
    *
    * @param other - The other image transformer to draw from
-   * @param x - X coordinate where to place the overlay
-   * @param y - Y coordinate where to place the overlay
+   * @param x - X coordinate where to place the overlay, which may be negative
+   * @param y - Y coordinate where to place the overlay, which may be negative
+   * @param options - Optional opacity and blend mode
+   */
+  overlay(other: ImageTransformer, x: number, y: number, options?: OverlayOptions | undefined | null): this
+  /**
+   * Draw text on top of the current image
+   *
+   * Text is clipped to the text box described by the options.
+   *
+   * The font is copied and parsed on every call, so drawing many strings with a large font file
+   * is best done with a subset of the font, or with newlines in a single call.
+   *
+   * @param font - A TrueType or OpenType font file
+   * @param text - The text to draw. Newlines start a new line
+   * @param options - Size, colour and layout of the text
+   */
+  drawText(font: Uint8Array, text: string, options: TextOptions): this
+  /**
+   * Get the format of the encoded image this transformer was created from
+   *
+   * @returns The detected format, or `null` if created from a raw pixel buffer
    */
-  overlay(other: ImageTransformer, x: number, y: number): this
+  getSourceFormat(): ImageFormat | null
   /** Get the current dimensions of the transformed image */
   getCurrentDimensions(): ImageInfo
   /**
@@ -86,14 +129,54 @@ export declare class ImageTransformer {
    * Danger: This is performed synchronously on the main thread, which can become a performance bottleneck. It is advised to use `toBuffer` whenever possible
    *
    * @param format - The pixel format to pack into the buffer
+   * @param options - Optional settings for packing the buffer
    */
-  toBufferSync(format: PixelFormat): ComputedImage
+  toBufferSync(format: PixelFormat, options?: BufferOptions | undefined | null): ComputedImage
   /**
    * Asynchronously convert the transformed image to a Buffer
    *
    * @param format - The pixel format to pack into the buffer
+   * @param options - Optional settings for packing the buffer
+   */
+  toBuffer(format: PixelFormat, options?: BufferOptions | undefined | null): Promise<ComputedImage>
+  /**
+   * Write the transformed image into an existing buffer, instead of allocating a new one
+   *
+   * Danger: This is performed synchronously on the main thread, which can become a performance bottleneck. It is advised to use `toBufferInto` whenever possible
+   *
+   * @param target - The buffer to write the pixels into
+   * @param format - The pixel format to write
+   * @param offset - Byte offset in the target to start writing at. Defaults to 0
+   * @param options - Optional settings for packing the buffer. Any padding from `stride` is left untouched
+   */
+  toBufferIntoSync(target: Uint8Array, format: PixelFormat, offset?: number | undefined | null, options?: BufferOptions | undefined | null): ImageInfo
+  /**
+   * Asynchronously write the transformed image into an existing buffer, instead of allocating a new one
+   *
+   * The pixels written must not be modified, and the target must not be transferred or detached,
+   * until the returned promise settles. Several renders may write to separate parts of the same
+   * target at the same time
+   *
+   * @param target - The buffer to write the pixels into
+   * @param format - The pixel format to write
+   * @param offset - Byte offset in the target to start writing at. Defaults to 0
+   * @param options - Optional settings for packing the buffer. Any padding from `stride` is left untouched
+   */
+  toBufferInto(target: Uint8Array, format: PixelFormat, offset?: number | undefined | null, options?: BufferOptions | undefined | null): Promise<ImageInfo>
+  /**
+   * Convert the transformed image to a 1-bit Buffer, with set bits for white pixels
+   *
+   * Danger: This is performed synchronously on the main thread, which can become a performance bottleneck. It is advised to use `toMonochrome` whenever possible
+   *
+   * @param options - Optional layout and dithering settings
+   */
+  toMonochromeSync(options?: MonochromeOptions | undefined | null): ComputedImage
+  /**
+   * Asynchronously convert the transformed image to a 1-bit Buffer, with set bits for white pixels
+   *
+   * @param options - Optional layout and dithering settings
    */
-  toBuffer(format: PixelFormat): Promise<ComputedImage>
+  toMonochrome(options?: MonochromeOptions | undefined | null): Promise<ComputedImage>
   /**
    * Convert the transformed image to an encoded image Buffer
    *
@@ -102,14 +185,14 @@ export declare class ImageTransformer {
    * @param format - The image format to pack into the buffer
    * @param options - Optional encoding options
    */
-  toEncodedImageSync(format: ImageFormat, options?: EncodingOptions | undefined | null): ComputedImage
+  toEncodedImageSync(format: OutputFormat, options?: EncodingOptions | undefined | null): ComputedImage
   /**
    * Asynchronously convert the transformed image to an encoded image Buffer
    *
    * @param format - The image format to pack into the buffer
    * @param options - Optional encoding options
    */
-  toEncodedImage(format: ImageFormat, options?: EncodingOptions | undefined | null): Promise<ComputedImage>
+  toEncodedImage(format: OutputFormat, options?: EncodingOptions | undefined | null): Promise<ComputedImage>
   /**
    * Convert the transformed image to a data URL string
    *
@@ -118,14 +201,46 @@ export declare class ImageTransformer {
    * @param format - The image format to encode
    * @param options - Optional encoding options
    */
-  toDataUrlSync(format: ImageFormat, options?: EncodingOptions | undefined | null): string
+  toDataUrlSync(format: OutputFormat, options?: EncodingOptions | undefined | null): string
   /**
    * Asynchronously convert the transformed image to a data URL string
    *
    * @param format - The image format to encode
    * @param options - Optional encoding options
    */
-  toDataUrl(format: ImageFormat, options?: EncodingOptions | undefined | null): Promise<string>
+  toDataUrl(format: OutputFormat, options?: EncodingOptions | undefined | null): Promise<string>
+}
+
+export type BitOrder =  'MsbFirst'|
+'LsbFirst';
+
+export type BlendMode =  'Normal'|
+'Multiply'|
+'Screen'|
+'Overlay'|
+'Darken'|
+'Lighten'|
+'Add'|
+'Difference'|
+'SoftLight';
+
+export interface BufferOptions {
+  /**
+   * Dithering to use when reducing the colour depth, for the packed 16-bit formats.
+   * Defaults to rounding each pixel to the nearest colour
+   */
+  dither?: Dither
+  /** Colour matrix for the yuv formats. Defaults to `Bt601` */
+  yuvMatrix?: YuvMatrix
+  /** Value range for the yuv formats. Defaults to `Limited` */
+  yuvRange?: YuvRange
+  /**
+   * Bytes from the start of one row to the start of the next, with the padding filled with
+   * zeros. Planar yuv formats follow the same rules as `fromBuffer`
+   */
+  stride?: number
+  /** Pad each row to a multiple of this many bytes, as an alternative to `stride` */
+  rowAlignment?: number
 }
 
 export interface ComputedImage {
@@ -134,27 +249,173 @@ export interface ComputedImage {
   height: number
 }
 
+export interface DecodeOptions {
+  /** Rotate and flip the image according to its EXIF orientation. Defaults to true */
+  applyOrientation?: boolean
+  /**
+   * Read the image straight from the given buffer instead of taking a copy. The buffer must
+   * not be modified while the transformer, or any image it is overlaid onto, can still be
+   * rendered. Has no effect for data URLs. Defaults to false
+   */
+  zeroCopy?: boolean
+}
+
+export type Dither =  'Ordered'|
+'FloydSteinberg'|
+'Atkinson';
+
 export interface EncodingOptions {
+  /** Quality from 0 to 1 for jpeg, lossy webp and avif */
   quality?: number
+  /** Use lossless compression for webp. Defaults to lossless unless a quality is given */
+  lossless?: boolean
+  /** Encoder speed for avif, from 1 (slowest, smallest output) to 10 (fastest). Defaults to 4 */
+  speed?: number
+  /**
+   * zlib compression level for png, from 0 (uncompressed) to 9 (smallest output). Defaults to
+   * fast compression
+   */
+  compressionLevel?: number
+  /** Row filter for png. Defaults to `Adaptive` */
+  pngFilter?: PngFilter
+  /** Write png as 8-bit indexed colour. Images with more than 256 colours are quantised */
+  indexed?: boolean
+  /** Chroma subsampling for jpeg. Defaults to `4:4:4`, which keeps full colour resolution */
+  subsampling?: JpegSubsampling
+  /** Write a progressive jpeg, which loads gradually in browsers. Defaults to baseline */
+  progressive?: boolean
+  /**
+   * Colour to flatten transparent areas onto for jpeg. Its alpha is ignored. When not set
+   * the alpha channel is discarded, exposing the colour of transparent pixels
+   */
+  background?: RgbaValue
+  /**
+   * Sizes of the images to include in an ico file, each between 1 and 256. Each image is
+   * fitted within a square of that size. Defaults to a single image at the current size
+   */
+  icoSizes?: Array<number>
 }
 
+export type Gravity =  'Center'|
+'Top'|
+'Bottom'|
+'Left'|
+'Right'|
+'TopLeft'|
+'TopRight'|
+'BottomLeft'|
+'BottomRight';
+
 export type ImageFormat =  'jpeg'|
 'webp'|
-'png';
+'png'|
+'gif'|
+'bmp'|
+'ico'|
+'tiff'|
+'tga'|
+'qoi'|
+'pnm'|
+'avif';
 
 export interface ImageInfo {
   width: number
   height: number
 }
 
+export type Interpolation =  'Nearest'|
+'Bilinear'|
+'Bicubic';
+
+export type JpegSubsampling =  '4:4:4'|
+'4:2:2'|
+'4:2:0';
+
+export type MonochromeLayout =  'RowMajor'|
+'PageMajor';
+
+export interface MonochromeOptions {
+  /** How pixels are arranged into bytes. Defaults to `RowMajor` */
+  layout?: MonochromeLayout
+  /** Which end of each byte holds the first pixel. Defaults to `MsbFirst` */
+  bitOrder?: BitOrder
+  /** Dithering to use for grey levels. Defaults to a plain threshold at mid grey */
+  dither?: Dither
+}
+
+/** Formats that can be encoded to. Decoding also supports `tga` and `pnm`, see `ImageFormat` */
+export type OutputFormat =  'jpeg'|
+'webp'|
+'png'|
+'gif'|
+'bmp'|
+'ico'|
+'tiff'|
+'qoi'|
+'avif';
+
+export interface OverlayOptions {
+  /** Opacity of the overlay, from 0 (invisible) to 1. Defaults to 1 */
+  opacity?: number
+  /** How the colours of the overlay combine with the image below. Defaults to `Normal` */
+  blendMode?: BlendMode
+}
+
 export type PixelFormat =  'rgba'|
 'rgb'|
 'bgra'|
-'bgr';
+'bgr'|
+'argb'|
+'abgr'|
+'luma'|
+'lumaAlpha'|
+'luma16'|
+'lumaAlpha16'|
+'rgb565'|
+'rgb565be'|
+'rgb555'|
+'rgb555be'|
+'nv12'|
+'i420'|
+'yuyv'|
+'uyvy';
+
+export type PngFilter =  'NoFilter'|
+'Sub'|
+'Up'|
+'Avg'|
+'Paeth'|
+'Adaptive';
+
+export type ResizeFilter =  'Nearest'|
+'Triangle'|
+'CatmullRom'|
+'Gaussian'|
+'Lanczos3';
 
 export type ResizeMode =  'Exact'|
 'Fill'|
-'Fit';
+'Fit'|
+'Contain';
+
+export interface RawImageOptions {
+  /** Colour matrix for the yuv formats. Defaults to `Bt601` */
+  yuvMatrix?: YuvMatrix
+  /** Value range for the yuv formats. Defaults to `Limited` */
+  yuvRange?: YuvRange
+  /**
+   * Bytes from the start of one row to the start of the next, when rows are padded. For nv12
+   * this applies to both planes, and for i420 the chroma planes use half of it, rounded up.
+   * Defaults to tightly packed rows
+   */
+  stride?: number
+  /**
+   * Read the pixels straight from the given buffer instead of taking a copy. The buffer must
+   * not be modified while the transformer, or any image it is overlaid onto, can still be
+   * rendered. Defaults to false
+   */
+  zeroCopy?: boolean
+}
 
 export interface RgbaValue {
   red: number
@@ -163,13 +424,70 @@ export interface RgbaValue {
   alpha: number
 }
 
+export interface RotateOptions {
+  /** Colour to fill the uncovered corners with. Defaults to transparent */
+  background?: RgbaValue
+  /** Grow the canvas to fit the whole rotated image. Defaults to false */
+  expand?: boolean
+  /** How to sample the source image. Defaults to `Bilinear` */
+  interpolation?: Interpolation
+}
+
 export type RotationMode =  'CW90'|
 'CW180'|
 'CW270';
 
+export interface ScaleOptions {
+  /** Resampling filter to use. Defaults to `Lanczos3` */
+  filter?: ResizeFilter
+  /** Which part of the image to keep for `Fill`, or where to position it for `Contain`. Defaults to `Center` */
+  gravity?: Gravity
+  /** Colour to fill the empty space with for `Contain`. Defaults to transparent */
+  background?: RgbaValue
+}
+
+export type SmartCropStrategy =  'Entropy'|
+'Attention';
+
+export type TextAlign =  'Left'|
+'Center'|
+'Right';
+
+export interface TextOptions {
+  /** Font size in pixels, up to 4096 */
+  size: number
+  color: RgbaValue
+  /** X coordinate of the text box. Defaults to 0 */
+  x?: number
+  /** Y coordinate of the text box. Defaults to 0 */
+  y?: number
+  /** Width of the text box. Defaults to the remaining width of the image */
+  width?: number
+  /** Height of the text box. Defaults to the remaining height of the image */
+  height?: number
+  /** Horizontal alignment of each line within the box. Defaults to `Left` */
+  align?: TextAlign
+  /** Vertical alignment of the block of text within the box. Defaults to `Top` */
+  verticalAlign?: TextVerticalAlign
+  /** Wrap lines on whitespace to fit the width of the box. Defaults to false */
+  wrap?: boolean
+  /** Line height as a multiple of the font's natural line height. Defaults to 1 */
+  lineHeight?: number
+}
+
+export type TextVerticalAlign =  'Top'|
+'Center'|
+'Bottom';
+
 export interface TransformOptions {
   scaleMode?: ResizeMode
   flipH?: boolean
   flipV?: boolean
   rotation?: RotationMode
 }
+
+export type YuvMatrix =  'Bt601'|
+'Bt709';
+
+export type YuvRange =  'Limited'|
+'Full';
@@ -266,10 +266,45 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`);
 }
 
-const { ImageTransformer, ImageFormat, PixelFormat, ResizeMode, RotationMode } =
-  nativeBinding;
+const {
+  ImageTransformer,
+  BitOrder,
+  BlendMode,
+  Dither,
+  Gravity,
+  ImageFormat,
+  Interpolation,
+  JpegSubsampling,
+  MonochromeLayout,
+  OutputFormat,
+  PixelFormat,
+  PngFilter,
+  ResizeFilter,
+  ResizeMode,
+  RotationMode,
+  SmartCropStrategy,
+  TextAlign,
+  TextVerticalAlign,
+  YuvMatrix,
+  YuvRange,
+} = nativeBinding;
 export { ImageTransformer };
+export { BitOrder };
+export { BlendMode };
+export { Dither };
+export { Gravity };
 export { ImageFormat };
+export { Interpolation };
+export { JpegSubsampling };
+export { MonochromeLayout };
+export { OutputFormat };
 export { PixelFormat };
+export { PngFilter };
+export { ResizeFilter };
 export { ResizeMode };
 export { RotationMode };
+export { SmartCropStrategy };
+export { TextAlign };
+export { TextVerticalAlign };
+export { YuvMatrix };
+export { YuvRange };
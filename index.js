@@ -1,7 +1,22 @@
 import * as bindings from "./bindings.js";
 
+export const BitOrder = bindings.BitOrder;
+export const BlendMode = bindings.BlendMode;
+export const Dither = bindings.Dither;
+export const Interpolation = bindings.Interpolation;
+export const JpegSubsampling = bindings.JpegSubsampling;
+export const MonochromeLayout = bindings.MonochromeLayout;
+export const OutputFormat = bindings.OutputFormat;
 export const PixelFormat = bindings.PixelFormat;
+export const PngFilter = bindings.PngFilter;
+export const Gravity = bindings.Gravity;
 export const ImageFormat = bindings.ImageFormat;
+export const ResizeFilter = bindings.ResizeFilter;
 export const ResizeMode = bindings.ResizeMode;
 export const RotationMode = bindings.RotationMode;
+export const SmartCropStrategy = bindings.SmartCropStrategy;
+export const TextAlign = bindings.TextAlign;
+export const TextVerticalAlign = bindings.TextVerticalAlign;
+export const YuvMatrix = bindings.YuvMatrix;
+export const YuvRange = bindings.YuvRange;
 export const ImageTransformer = bindings.ImageTransformer;
@@ -0,0 +1,154 @@
+// Blend modes and opacity for the `overlay` transform step
+
+use image::{ColorType, DynamicImage, ImageBuffer, Pixel, Rgba};
+
+use crate::BlendMode;
+
+/// Blend a single colour channel, with both values in the range [0, 1]. These follow the
+/// separable blend modes of the W3C compositing spec, with `Add` clamped to white
+fn blend_channel(mode: BlendMode, bottom: f32, top: f32) -> f32 {
+  match mode {
+    BlendMode::Normal => top,
+    BlendMode::Multiply => bottom * top,
+    BlendMode::Screen => bottom + top - bottom * top,
+    BlendMode::Overlay => {
+      if bottom <= 0.5 {
+        2.0 * bottom * top
+      } else {
+        let bottom = 2.0 * bottom - 1.0;
+        bottom + top - bottom * top
+      }
+    }
+    BlendMode::Darken => bottom.min(top),
+    BlendMode::Lighten => bottom.max(top),
+    BlendMode::Add => (bottom + top).min(1.0),
+    BlendMode::Difference => (bottom - top).abs(),
+    BlendMode::SoftLight => {
+      if top <= 0.5 {
+        bottom - (1.0 - 2.0 * top) * bottom * (1.0 - bottom)
+      } else {
+        let d = if bottom <= 0.25 {
+          ((16.0 * bottom - 12.0) * bottom + 4.0) * bottom
+        } else {
+          bottom.sqrt()
+        };
+        bottom + (2.0 * top - 1.0) * (d - bottom)
+      }
+    }
+  }
+}
+
+/// Composite one pixel over another, with every channel in the range [0, 1]. The blended
+/// colour is only used where both pixels are opaque, falling back to the plain top or bottom
+/// colour where either is transparent. A transparent top pixel leaves the bottom one unchanged
+fn blend_rgba(mode: BlendMode, bottom: [f32; 4], top: [f32; 4], opacity: f32) -> [f32; 4] {
+  let [bottom_color @ .., bottom_alpha] = bottom;
+  let [top_color @ .., top_alpha] = top;
+  let top_alpha = top_alpha * opacity;
+
+  if top_alpha <= 0.0 {
+    return bottom;
+  }
+  let alpha = top_alpha + bottom_alpha * (1.0 - top_alpha);
+
+  let channel = |i: usize| {
+    let (b, t) = (bottom_color[i], top_color[i]);
+    let color = top_alpha * (1.0 - bottom_alpha) * t
+      + top_alpha * bottom_alpha * blend_channel(mode, b, t)
+      + (1.0 - top_alpha) * bottom_alpha * b;
+    color / alpha
+  };
+
+  [channel(0), channel(1), channel(2), alpha]
+}
+
+/// Composite one 8-bit pixel over another.
+///
+/// Unlike `Pixel::blend` this rounds rather than truncates, which would otherwise leave an
+/// opaque background at alpha 254
+pub(crate) fn blend_pixel(
+  mode: BlendMode,
+  bottom: Rgba<u8>,
+  top: Rgba<u8>,
+  opacity: f32,
+) -> Rgba<u8> {
+  let blended = blend_rgba(
+    mode,
+    bottom.0.map(|value| value as f32 / 255.0),
+    top.0.map(|value| value as f32 / 255.0),
+    opacity,
+  );
+
+  Rgba(blended.map(|value| (value * 255.0).round().clamp(0.0, 255.0) as u8))
+}
+
+/// Blend each pixel of `top` that lands on `bottom`, with its top left corner at (x, y)
+fn blend_region<P: Pixel>(
+  bottom: &mut ImageBuffer<P, Vec<P::Subpixel>>,
+  top: &ImageBuffer<P, Vec<P::Subpixel>>,
+  x: i64,
+  y: i64,
+  blend: impl Fn(P, P) -> P,
+) {
+  // Range of rows and columns of `top` that land on `bottom`
+  let start_x = (-x).max(0);
+  let start_y = (-y).max(0);
+  let end_x = (bottom.width() as i64 - x).min(top.width() as i64);
+  let end_y = (bottom.height() as i64 - y).min(top.height() as i64);
+
+  for top_y in start_y..end_y {
+    for top_x in start_x..end_x {
+      let pixel = bottom.get_pixel_mut((x + top_x) as u32, (y + top_y) as u32);
+      *pixel = blend(*pixel, *top.get_pixel(top_x as u32, top_y as u32));
+    }
+  }
+}
+
+/// Draw `top` over `bottom` with its top left corner at (x, y). Parts of `top` outside of
+/// `bottom` are skipped.
+///
+/// Rgba8 images are blended as they are. Anything else is blended as rgba32f, and converted
+/// back to its own pixel type afterwards so that no precision is lost
+pub(crate) fn blend_overlay(
+  bottom: DynamicImage,
+  top: &DynamicImage,
+  x: i64,
+  y: i64,
+  opacity: f32,
+  mode: BlendMode,
+) -> DynamicImage {
+  if let DynamicImage::ImageRgba8(mut canvas) = bottom {
+    let converted;
+    let top = match top.as_rgba8() {
+      Some(top) => top,
+      None => {
+        converted = top.to_rgba8();
+        &converted
+      }
+    };
+
+    blend_region(&mut canvas, top, x, y, |bottom, top| {
+      blend_pixel(mode, bottom, top, opacity)
+    });
+    return DynamicImage::from(canvas);
+  }
+
+  let color = bottom.color();
+  let mut canvas = bottom.into_rgba32f();
+  blend_region(&mut canvas, &top.to_rgba32f(), x, y, |bottom, top| {
+    Rgba(blend_rgba(mode, bottom.0, top.0, opacity))
+  });
+
+  let canvas = DynamicImage::from(canvas);
+  match color {
+    ColorType::L8 => DynamicImage::from(canvas.into_luma8()),
+    ColorType::La8 => DynamicImage::from(canvas.into_luma_alpha8()),
+    ColorType::Rgb8 => DynamicImage::from(canvas.into_rgb8()),
+    ColorType::L16 => DynamicImage::from(canvas.into_luma16()),
+    ColorType::La16 => DynamicImage::from(canvas.into_luma_alpha16()),
+    ColorType::Rgb16 => DynamicImage::from(canvas.into_rgb16()),
+    ColorType::Rgba16 => DynamicImage::from(canvas.into_rgba16()),
+    ColorType::Rgb32F => DynamicImage::from(canvas.into_rgb32f()),
+    _ => canvas,
+  }
+}
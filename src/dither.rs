@@ -0,0 +1,93 @@
+// Dithering helpers for output formats with reduced colour depth
+
+use crate::Dither;
+
+/// 8x8 Bayer matrix, with each threshold level appearing once
+const BAYER_8X8: [[u8; 8]; 8] = [
+  [0, 32, 8, 40, 2, 34, 10, 42],
+  [48, 16, 56, 24, 50, 18, 58, 26],
+  [12, 44, 4, 36, 14, 46, 6, 38],
+  [60, 28, 52, 20, 62, 30, 54, 22],
+  [3, 35, 11, 43, 1, 33, 9, 41],
+  [51, 19, 59, 27, 49, 17, 57, 25],
+  [15, 47, 7, 39, 13, 45, 5, 37],
+  [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Neighbours that receive the error of a pixel, as (dx, dy, weight)
+const FLOYD_STEINBERG: [(i64, i64, f32); 4] = [
+  (1, 0, 7.0 / 16.0),
+  (-1, 1, 3.0 / 16.0),
+  (0, 1, 5.0 / 16.0),
+  (1, 1, 1.0 / 16.0),
+];
+
+/// Atkinson only passes on 3/4 of the error, which keeps more contrast than Floyd-Steinberg
+const ATKINSON: [(i64, i64, f32); 6] = [
+  (1, 0, 1.0 / 8.0),
+  (2, 0, 1.0 / 8.0),
+  (-1, 1, 1.0 / 8.0),
+  (0, 1, 1.0 / 8.0),
+  (1, 1, 1.0 / 8.0),
+  (0, 2, 1.0 / 8.0),
+];
+
+/// Ordered dither threshold for a pixel, in the range (0, 1)
+fn ordered_threshold(x: usize, y: usize) -> f32 {
+  (BAYER_8X8[y % 8][x % 8] as f32 + 0.5) / 64.0
+}
+
+/// Reduce a plane of 8-bit values to levels from 0 to `max`, one row of `width` values at a
+/// time. Without dithering each value is rounded to the nearest level
+pub(crate) fn quantize_plane(
+  values: &[u8],
+  width: usize,
+  max: u16,
+  dither: Option<Dither>,
+) -> Vec<u16> {
+  let scale = max as f32 / 255.0;
+
+  match dither {
+    None => values
+      .iter()
+      .map(|value| (*value as f32 * scale).round() as u16)
+      .collect(),
+    Some(Dither::Ordered) => values
+      .iter()
+      .enumerate()
+      .map(|(i, value)| {
+        let threshold = ordered_threshold(i % width, i / width);
+        ((*value as f32 * scale + threshold).floor() as u16).min(max)
+      })
+      .collect(),
+    Some(Dither::FloydSteinberg) => diffuse_error(values, width, max, &FLOYD_STEINBERG),
+    Some(Dither::Atkinson) => diffuse_error(values, width, max, &ATKINSON),
+  }
+}
+
+fn diffuse_error(values: &[u8], width: usize, max: u16, kernel: &[(i64, i64, f32)]) -> Vec<u16> {
+  let scale = max as f32 / 255.0;
+  let height = values.len().checked_div(width).unwrap_or(0);
+
+  let mut levels: Vec<f32> = values.iter().map(|value| *value as f32 * scale).collect();
+  let mut output = Vec::with_capacity(levels.len());
+
+  for y in 0..height {
+    for x in 0..width {
+      let value = levels[y * width + x];
+      let level = value.round().clamp(0.0, max as f32);
+      let error = value - level;
+      output.push(level as u16);
+
+      for &(dx, dy, weight) in kernel {
+        let nx = x as i64 + dx;
+        let ny = y as i64 + dy;
+        if nx >= 0 && (nx as usize) < width && (ny as usize) < height {
+          levels[ny as usize * width + nx as usize] += error * weight;
+        }
+      }
+    }
+  }
+
+  output
+}
@@ -0,0 +1,49 @@
+// 1-bit packed output, as used by e-ink panels and small OLED displays
+
+use image::GrayImage;
+
+use crate::dither::quantize_plane;
+use crate::{BitOrder, Dither, MonochromeLayout};
+
+/// Pack a greyscale image into 1 bit per pixel, with set bits for white. Rows or pages that
+/// don't fill a whole byte are padded with unset bits
+pub(crate) fn pack(
+  img: &GrayImage,
+  layout: MonochromeLayout,
+  bit_order: BitOrder,
+  dither: Option<Dither>,
+) -> Vec<u8> {
+  let width = img.width() as usize;
+  let height = img.height() as usize;
+  let bits = quantize_plane(img.as_raw(), width, 1, dither);
+
+  let mask = |index: usize| match bit_order {
+    BitOrder::MsbFirst => 0x80 >> index,
+    BitOrder::LsbFirst => 1 << index,
+  };
+
+  match layout {
+    MonochromeLayout::RowMajor => {
+      let stride = width.div_ceil(8);
+      let mut data = vec![0u8; stride * height];
+      for (i, bit) in bits.iter().enumerate() {
+        if *bit != 0 {
+          let (x, y) = (i % width, i / width);
+          data[y * stride + x / 8] |= mask(x % 8);
+        }
+      }
+      data
+    }
+    MonochromeLayout::PageMajor => {
+      // Each byte is a column of 8 rows, and each page is a band of 8 rows
+      let mut data = vec![0u8; height.div_ceil(8) * width];
+      for (i, bit) in bits.iter().enumerate() {
+        if *bit != 0 {
+          let (x, y) = (i % width, i / width);
+          data[(y / 8) * width + x] |= mask(y % 8);
+        }
+      }
+      data
+    }
+  }
+}
@@ -0,0 +1,100 @@
+// Packed 16-bit rgb formats, as used by small embedded displays
+
+use image::{Rgb, RgbImage};
+
+use crate::Dither;
+use crate::dither::quantize_plane;
+
+#[derive(Clone, Copy)]
+pub(crate) struct PackedLayout {
+  /// Bits used for the green channel. Red and blue always use 5
+  pub green_bits: u32,
+  pub big_endian: bool,
+}
+
+impl PackedLayout {
+  pub const RGB565: Self = PackedLayout {
+    green_bits: 6,
+    big_endian: false,
+  };
+  pub const RGB565_BE: Self = PackedLayout {
+    green_bits: 6,
+    big_endian: true,
+  };
+  pub const RGB555: Self = PackedLayout {
+    green_bits: 5,
+    big_endian: false,
+  };
+  pub const RGB555_BE: Self = PackedLayout {
+    green_bits: 5,
+    big_endian: true,
+  };
+}
+
+/// Expand a channel back to 8 bits, replicating the high bits into the low bits so that
+/// the full range is covered
+fn expand_channel(value: u16, bits: u32) -> u8 {
+  let value = value as u32;
+  ((value << (8 - bits)) | (value >> (2 * bits - 8))) as u8
+}
+
+pub(crate) fn pack(img: &RgbImage, layout: PackedLayout, dither: Option<Dither>) -> Vec<u8> {
+  let width = img.width() as usize;
+  let channel = |index: usize, bits: u32| {
+    let values: Vec<u8> = img.pixels().map(|pixel| pixel.0[index]).collect();
+    quantize_plane(&values, width, (1u16 << bits) - 1, dither)
+  };
+
+  let red = channel(0, 5);
+  let green = channel(1, layout.green_bits);
+  let blue = channel(2, 5);
+
+  let mut data = Vec::with_capacity(red.len() * 2);
+  for ((red, green), blue) in red.into_iter().zip(green).zip(blue) {
+    let value = (red << (layout.green_bits + 5)) | (green << 5) | blue;
+    if layout.big_endian {
+      data.extend_from_slice(&value.to_be_bytes());
+    } else {
+      data.extend_from_slice(&value.to_le_bytes());
+    }
+  }
+
+  data
+}
+
+pub(crate) fn unpack(
+  data: &[u8],
+  width: u32,
+  height: u32,
+  layout: PackedLayout,
+) -> Option<RgbImage> {
+  let pixel_count = width as usize * height as usize;
+  if data.len() < pixel_count * 2 {
+    return None;
+  }
+
+  let green_mask = (1u16 << layout.green_bits) - 1;
+  let pixels = data[..pixel_count * 2]
+    .chunks_exact(2)
+    .flat_map(|bytes| {
+      let value = if layout.big_endian {
+        u16::from_be_bytes([bytes[0], bytes[1]])
+      } else {
+        u16::from_le_bytes([bytes[0], bytes[1]])
+      };
+
+      let red = (value >> (layout.green_bits + 5)) & 0x1f;
+      let green = (value >> 5) & green_mask;
+      let blue = value & 0x1f;
+
+      Rgb([
+        expand_channel(red, 5),
+        expand_channel(green, layout.green_bits),
+        expand_channel(blue, 5),
+      ])
+      .0
+    })
+    .collect();
+
+  RgbImage::from_raw(width, height, pixels)
+}
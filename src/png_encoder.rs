@@ -0,0 +1,151 @@
+// PNG encoding, with control over compression and optional palette output
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use color_quant::NeuQuant;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{DynamicImage, ImageError, ImageResult, RgbaImage};
+
+use crate::PngFilter;
+
+/// Number of pixels the palette quantiser learns from, at most. Larger images are sampled
+const QUANTIZE_SAMPLE_PIXELS: u64 = 65536;
+
+impl From<PngFilter> for FilterType {
+  fn from(filter: PngFilter) -> Self {
+    match filter {
+      PngFilter::NoFilter => FilterType::NoFilter,
+      PngFilter::Sub => FilterType::Sub,
+      PngFilter::Up => FilterType::Up,
+      PngFilter::Avg => FilterType::Avg,
+      PngFilter::Paeth => FilterType::Paeth,
+      PngFilter::Adaptive => FilterType::Adaptive,
+    }
+  }
+}
+
+impl From<PngFilter> for png::Filter {
+  fn from(filter: PngFilter) -> Self {
+    match filter {
+      PngFilter::NoFilter => png::Filter::NoFilter,
+      PngFilter::Sub => png::Filter::Sub,
+      PngFilter::Up => png::Filter::Up,
+      PngFilter::Avg => png::Filter::Avg,
+      PngFilter::Paeth => png::Filter::Paeth,
+      PngFilter::Adaptive => png::Filter::Adaptive,
+    }
+  }
+}
+
+pub(crate) struct PngOptions {
+  /// zlib compression level, from 0 (uncompressed) to 9. Uses fast compression when unset
+  pub compression_level: Option<u32>,
+  pub filter: PngFilter,
+  pub indexed: bool,
+}
+
+pub(crate) fn encode_png<W: Write>(
+  img: DynamicImage,
+  options: &PngOptions,
+  writer: W,
+) -> ImageResult<()> {
+  if options.indexed {
+    return encode_indexed(img.into_rgba8(), options, writer);
+  }
+
+  let compression = match options.compression_level {
+    None => CompressionType::Fast,
+    Some(0) => CompressionType::Uncompressed,
+    Some(level) => CompressionType::Level(level.min(9) as u8),
+  };
+
+  img.write_with_encoder(PngEncoder::new_with_quality(
+    writer,
+    compression,
+    options.filter.into(),
+  ))
+}
+
+/// Build a palette for the image, along with the palette index of every pixel.
+///
+/// Images with up to 256 distinct colours are stored exactly, otherwise the colours are quantised.
+fn build_palette(img: &RgbaImage) -> (Vec<[u8; 4]>, Vec<u8>) {
+  let mut palette: Vec<[u8; 4]> = Vec::new();
+  let mut lookup: HashMap<[u8; 4], u8> = HashMap::new();
+  let mut indices = Vec::with_capacity(img.width() as usize * img.height() as usize);
+
+  for pixel in img.pixels() {
+    let index = match lookup.get(&pixel.0) {
+      Some(index) => *index,
+      None if palette.len() < 256 => {
+        let index = palette.len() as u8;
+        palette.push(pixel.0);
+        lookup.insert(pixel.0, index);
+        index
+      }
+      None => return quantize(img),
+    };
+    indices.push(index);
+  }
+
+  (palette, indices)
+}
+
+fn quantize(img: &RgbaImage) -> (Vec<[u8; 4]>, Vec<u8>) {
+  // Sampling factor from 1 (every pixel) to 30 (every 30th pixel)
+  let pixels = img.width() as u64 * img.height() as u64;
+  let sample_factor = pixels.div_ceil(QUANTIZE_SAMPLE_PIXELS).clamp(1, 30) as i32;
+
+  let quantizer = NeuQuant::new(sample_factor, 256, img.as_raw());
+
+  let palette = quantizer
+    .color_map_rgba()
+    .chunks_exact(4)
+    .map(|color| [color[0], color[1], color[2], color[3]])
+    .collect();
+  let indices = img
+    .pixels()
+    .map(|pixel| quantizer.index_of(&pixel.0) as u8)
+    .collect();
+
+  (palette, indices)
+}
+
+fn encode_indexed<W: Write>(img: RgbaImage, options: &PngOptions, writer: W) -> ImageResult<()> {
+  let (palette, indices) = build_palette(&img);
+
+  let mut encoder = png::Encoder::new(writer, img.width(), img.height());
+  encoder.set_color(png::ColorType::Indexed);
+  encoder.set_depth(png::BitDepth::Eight);
+
+  let rgb: Vec<u8> = palette
+    .iter()
+    .flat_map(|color| &color[..3])
+    .copied()
+    .collect();
+  encoder.set_palette(rgb);
+
+  // Alpha values are only needed up to the last translucent entry
+  if let Some(last) = palette.iter().rposition(|color| color[3] != 255) {
+    let alpha: Vec<u8> = palette[..=last].iter().map(|color| color[3]).collect();
+    encoder.set_trns(alpha);
+  }
+
+  match options.compression_level {
+    None => encoder.set_compression(png::Compression::Fast),
+    Some(0) => encoder.set_compression(png::Compression::NoCompression),
+    Some(level) => {
+      encoder.set_deflate_compression(png::DeflateCompression::Level(level.min(9) as u8))
+    }
+  }
+  encoder.set_filter(options.filter.into());
+
+  let mut writer = encoder
+    .write_header()
+    .map_err(|e| ImageError::IoError(e.into()))?;
+  writer
+    .write_image_data(&indices)
+    .map_err(|e| ImageError::IoError(e.into()))?;
+  writer.finish().map_err(|e| ImageError::IoError(e.into()))
+}
@@ -0,0 +1,177 @@
+// Arbitrary angle rotation for the `rotateDegrees` transform step
+
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use crate::{Interpolation, RotateOp};
+
+/// Normalise an angle in degrees to the range [0, 360)
+fn normalise_angle(angle: f64) -> f64 {
+  let angle = angle.rem_euclid(360.0);
+  // rem_euclid can round up to exactly 360 for tiny negative angles
+  if angle >= 360.0 { 0.0 } else { angle }
+}
+
+/// Size of the image after rotating. Without `expand` the canvas keeps its original size
+pub(crate) fn rotated_size(width: u32, height: u32, angle: f64, expand: bool) -> (u32, u32) {
+  if !expand {
+    return (width, height);
+  }
+
+  let radians = normalise_angle(angle).to_radians();
+  let (sin, cos) = (radians.sin().abs(), radians.cos().abs());
+
+  let new_width = width as f64 * cos + height as f64 * sin;
+  let new_height = width as f64 * sin + height as f64 * cos;
+
+  // Allow a little floating point error so that multiples of 90 give exact sizes
+  (
+    ((new_width - 1e-6).ceil() as u32).max(1),
+    ((new_height - 1e-6).ceil() as u32).max(1),
+  )
+}
+
+fn premultiply(pixel: Rgba<u8>) -> [f32; 4] {
+  let [r, g, b, a] = pixel.0.map(f32::from);
+  let alpha = a / 255.0;
+  [r * alpha, g * alpha, b * alpha, a]
+}
+
+fn unpremultiply(pixel: [f32; 4]) -> Rgba<u8> {
+  let alpha = pixel[3].clamp(0.0, 255.0);
+  if alpha <= 0.0 {
+    return Rgba([0, 0, 0, 0]);
+  }
+
+  let scale = 255.0 / alpha;
+  Rgba([
+    (pixel[0] * scale).round().clamp(0.0, 255.0) as u8,
+    (pixel[1] * scale).round().clamp(0.0, 255.0) as u8,
+    (pixel[2] * scale).round().clamp(0.0, 255.0) as u8,
+    alpha.round() as u8,
+  ])
+}
+
+/// Catmull-Rom cubic weight
+fn cubic_weight(x: f32) -> f32 {
+  let x = x.abs();
+  if x < 1.0 {
+    1.5 * x * x * x - 2.5 * x * x + 1.0
+  } else if x < 2.0 {
+    -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+  } else {
+    0.0
+  }
+}
+
+struct Sampler<'a> {
+  source: &'a RgbaImage,
+  background: [f32; 4],
+}
+
+impl Sampler<'_> {
+  /// Premultiplied pixel, treating everything outside of the source as the background
+  fn pixel(&self, x: i64, y: i64) -> [f32; 4] {
+    if x < 0 || y < 0 || x >= self.source.width() as i64 || y >= self.source.height() as i64 {
+      self.background
+    } else {
+      premultiply(*self.source.get_pixel(x as u32, y as u32))
+    }
+  }
+
+  /// Sample at a position in pixel space, where pixel centres are at +0.5
+  fn sample(&self, x: f32, y: f32, interpolation: Interpolation) -> [f32; 4] {
+    match interpolation {
+      Interpolation::Nearest => self.pixel(x.floor() as i64, y.floor() as i64),
+      Interpolation::Bilinear => {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut out = [0.0; 4];
+        for (dy, wy) in [(0, 1.0 - fy), (1, fy)] {
+          for (dx, wx) in [(0, 1.0 - fx), (1, fx)] {
+            let pixel = self.pixel(x0 + dx, y0 + dy);
+            for c in 0..4 {
+              out[c] += pixel[c] * wx * wy;
+            }
+          }
+        }
+        out
+      }
+      Interpolation::Bicubic => {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut out = [0.0; 4];
+        for dy in -1..=2 {
+          let wy = cubic_weight(dy as f32 - fy);
+          for dx in -1..=2 {
+            let wx = cubic_weight(dx as f32 - fx);
+            let pixel = self.pixel(x0 + dx, y0 + dy);
+            for c in 0..4 {
+              out[c] += pixel[c] * wx * wy;
+            }
+          }
+        }
+        // Cubic overshoot can push colour above alpha, which is not valid premultiplied data
+        let alpha = out[3].clamp(0.0, 255.0);
+        [
+          out[0].clamp(0.0, alpha),
+          out[1].clamp(0.0, alpha),
+          out[2].clamp(0.0, alpha),
+          alpha,
+        ]
+      }
+    }
+  }
+}
+
+pub(crate) fn rotate_image(img: DynamicImage, op: &RotateOp) -> DynamicImage {
+  let angle = normalise_angle(op.angle);
+
+  // Quarter turns can be done exactly when the canvas is allowed to change shape
+  if angle == 0.0 {
+    return img;
+  } else if op.expand || img.width() == img.height() {
+    if angle == 90.0 {
+      return img.rotate90();
+    } else if angle == 270.0 {
+      return img.rotate270();
+    }
+  }
+  if angle == 180.0 {
+    return img.rotate180();
+  }
+
+  let source = img.into_rgba8();
+  let (width, height) = rotated_size(source.width(), source.height(), angle, op.expand);
+
+  let sampler = Sampler {
+    source: &source,
+    background: premultiply(op.background),
+  };
+
+  // Positive angles rotate clockwise, matching `RotationMode`
+  let radians = angle.to_radians();
+  let (sin, cos) = (radians.sin() as f32, radians.cos() as f32);
+
+  let source_center_x = source.width() as f32 / 2.0;
+  let source_center_y = source.height() as f32 / 2.0;
+  let center_x = width as f32 / 2.0;
+  let center_y = height as f32 / 2.0;
+
+  let rotated = RgbaImage::from_fn(width, height, |x, y| {
+    // Map each output pixel centre back into the source
+    let dx = x as f32 + 0.5 - center_x;
+    let dy = y as f32 + 0.5 - center_y;
+    let source_x = dx * cos + dy * sin + source_center_x;
+    let source_y = -dx * sin + dy * cos + source_center_y;
+
+    unpremultiply(sampler.sample(source_x, source_y, op.interpolation))
+  });
+
+  DynamicImage::from(rotated)
+}
@@ -0,0 +1,185 @@
+// Content aware crop window selection for the `smartCrop` transform step
+//
+// The analysis runs on a small downscaled copy of the image, with summed area tables so
+// that every candidate window position can be scored in constant time.
+
+use image::{DynamicImage, RgbImage};
+
+use crate::SmartCropStrategy;
+
+/// Longest edge of the copy of the image that is analysed
+const ANALYSIS_SIZE: u32 = 128;
+
+/// Number of luma buckets used for the entropy histogram
+const ENTROPY_BINS: usize = 32;
+
+/// Summed area table, with an extra leading row and column of zeros
+struct Integral {
+  width: usize,
+  values: Vec<f64>,
+}
+
+impl Integral {
+  fn new(width: u32, height: u32, value: impl Fn(u32, u32) -> f64) -> Self {
+    let stride = width as usize + 1;
+    let mut values = vec![0.0; stride * (height as usize + 1)];
+
+    for y in 0..height {
+      let mut row_sum = 0.0;
+      for x in 0..width {
+        row_sum += value(x, y);
+        let i = (y as usize + 1) * stride + x as usize + 1;
+        values[i] = values[i - stride] + row_sum;
+      }
+    }
+
+    Integral {
+      width: stride,
+      values,
+    }
+  }
+
+  fn sum(&self, x: u32, y: u32, width: u32, height: u32) -> f64 {
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = (x0 + width as usize, y0 + height as usize);
+
+    self.values[y1 * self.width + x1]
+      - self.values[y0 * self.width + x1]
+      - self.values[y1 * self.width + x0]
+      + self.values[y0 * self.width + x0]
+  }
+}
+
+fn luma(pixel: &[u8; 3]) -> f64 {
+  0.2126 * pixel[0] as f64 + 0.7152 * pixel[1] as f64 + 0.0722 * pixel[2] as f64
+}
+
+/// Per pixel interest: a weighted mix of edge density, skin tones and saturation
+fn attention_map(img: &RgbImage) -> Integral {
+  let (width, height) = img.dimensions();
+  let luma_at = |x: u32, y: u32| luma(&img.get_pixel(x, y).0);
+
+  Integral::new(width, height, |x, y| {
+    let pixel = img.get_pixel(x, y).0;
+    let [r, g, b] = pixel.map(i32::from);
+
+    // Central difference gradient, clamped at the image edges
+    let dx = luma_at((x + 1).min(width - 1), y) - luma_at(x.saturating_sub(1), y);
+    let dy = luma_at(x, (y + 1).min(height - 1)) - luma_at(x, y.saturating_sub(1));
+    let edge = ((dx * dx + dy * dy).sqrt() / 255.0).min(1.0);
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let saturation = if max == 0 {
+      0.0
+    } else {
+      (max - min) as f64 / max as f64
+    };
+
+    // Classic rgb skin tone rule
+    let skin = r > 95 && g > 40 && b > 20 && max - min > 15 && (r - g).abs() > 15 && r > g && r > b;
+
+    edge * 2.0 + saturation * 0.5 + if skin { 1.0 } else { 0.0 }
+  })
+}
+
+/// One summed area table per luma bucket
+fn entropy_maps(img: &RgbImage) -> Vec<Integral> {
+  let (width, height) = img.dimensions();
+  let bins: Vec<usize> = img
+    .pixels()
+    .map(|pixel| ((luma(&pixel.0) as usize) * ENTROPY_BINS / 256).min(ENTROPY_BINS - 1))
+    .collect();
+
+  (0..ENTROPY_BINS)
+    .map(|bin| {
+      Integral::new(width, height, |x, y| {
+        if bins[(y * width + x) as usize] == bin {
+          1.0
+        } else {
+          0.0
+        }
+      })
+    })
+    .collect()
+}
+
+fn window_entropy(maps: &[Integral], x: u32, y: u32, width: u32, height: u32) -> f64 {
+  let total = (width * height) as f64;
+
+  maps
+    .iter()
+    .map(|map| map.sum(x, y, width, height) / total)
+    .filter(|p| *p > 0.0)
+    .map(|p| -p * p.log2())
+    .sum()
+}
+
+/// Choose the top-left corner of the most interesting `width` x `height` window
+pub(crate) fn find_crop(
+  img: &DynamicImage,
+  width: u32,
+  height: u32,
+  strategy: SmartCropStrategy,
+) -> (u32, u32) {
+  let (img_width, img_height) = (img.width(), img.height());
+  if width >= img_width && height >= img_height {
+    return (0, 0);
+  }
+
+  let scale = f64::min(
+    1.0,
+    ANALYSIS_SIZE as f64 / f64::max(img_width as f64, img_height as f64),
+  );
+  let analysis_width = ((img_width as f64 * scale).round() as u32).max(1);
+  let analysis_height = ((img_height as f64 * scale).round() as u32).max(1);
+  let analysis = img
+    .thumbnail_exact(analysis_width, analysis_height)
+    .into_rgb8();
+
+  let window_width = ((width as f64 * scale).round() as u32).clamp(1, analysis_width);
+  let window_height = ((height as f64 * scale).round() as u32).clamp(1, analysis_height);
+
+  let score: Box<dyn Fn(u32, u32) -> f64> = match strategy {
+    SmartCropStrategy::Attention => {
+      let map = attention_map(&analysis);
+      Box::new(move |x, y| map.sum(x, y, window_width, window_height))
+    }
+    SmartCropStrategy::Entropy => {
+      let maps = entropy_maps(&analysis);
+      Box::new(move |x, y| window_entropy(&maps, x, y, window_width, window_height))
+    }
+  };
+
+  // Ties (e.g. a featureless image) are resolved in favour of the most central window
+  let center_x = (analysis_width - window_width) as f64 / 2.0;
+  let center_y = (analysis_height - window_height) as f64 / 2.0;
+
+  let mut best: Option<((u32, u32), f64, f64)> = None;
+  for y in 0..=(analysis_height - window_height) {
+    for x in 0..=(analysis_width - window_width) {
+      let score = score(x, y);
+      let distance = (x as f64 - center_x).powi(2) + (y as f64 - center_y).powi(2);
+
+      let is_better = match best {
+        None => true,
+        Some((_, best_score, best_distance)) => {
+          let epsilon = 1e-9 * best_score.abs().max(1.0);
+          score > best_score + epsilon
+            || (score >= best_score - epsilon && distance < best_distance)
+        }
+      };
+      if is_better {
+        best = Some(((x, y), score, distance));
+      }
+    }
+  }
+
+  let ((best_x, best_y), _, _) = best.unwrap_or(((0, 0), 0.0, 0.0));
+
+  // Map the window back to full resolution, keeping it inside the image
+  let x = ((best_x as f64 / scale).round() as u32).min(img_width.saturating_sub(width));
+  let y = ((best_y as f64 / scale).round() as u32).min(img_height.saturating_sub(height));
+
+  (x, y)
+}
@@ -0,0 +1,154 @@
+// Row strides for raw pixel buffers, where each row may be followed by padding
+
+use crate::PixelFormat;
+
+/// A run of rows within a buffer. Planar formats are made up of several
+pub(crate) struct Plane {
+  /// Bytes of pixel data in each row
+  pub row_bytes: usize,
+  /// Bytes from the start of one row to the start of the next
+  pub stride: usize,
+  pub rows: usize,
+}
+
+/// Bytes needed for one row of pixels without padding, or of the luma plane for planar formats
+pub(crate) fn row_bytes(format: PixelFormat, width: u32) -> usize {
+  let width = width as usize;
+
+  match format {
+    PixelFormat::rgba | PixelFormat::bgra | PixelFormat::argb | PixelFormat::abgr => width * 4,
+    PixelFormat::rgb | PixelFormat::bgr => width * 3,
+    PixelFormat::luma | PixelFormat::nv12 | PixelFormat::i420 => width,
+    PixelFormat::lumaAlpha
+    | PixelFormat::luma16
+    | PixelFormat::rgb565
+    | PixelFormat::rgb565be
+    | PixelFormat::rgb555
+    | PixelFormat::rgb555be => width * 2,
+    PixelFormat::lumaAlpha16 => width * 4,
+    PixelFormat::yuyv | PixelFormat::uyvy => width.div_ceil(2) * 4,
+  }
+}
+
+/// Layout of the planes of an image, where `stride` is the stride of the first plane.
+///
+/// The interleaved chroma plane of nv12 uses the same stride as the luma plane, while the
+/// chroma planes of i420 use half of it, rounded up
+pub(crate) fn planes(format: PixelFormat, width: u32, height: u32, stride: usize) -> Vec<Plane> {
+  let luma = Plane {
+    row_bytes: row_bytes(format, width),
+    stride,
+    rows: height as usize,
+  };
+  let chroma_width = (width as usize).div_ceil(2);
+  let chroma_rows = (height as usize).div_ceil(2);
+
+  match format {
+    PixelFormat::nv12 => vec![
+      luma,
+      Plane {
+        row_bytes: chroma_width * 2,
+        stride,
+        rows: chroma_rows,
+      },
+    ],
+    PixelFormat::i420 => {
+      let chroma = || Plane {
+        row_bytes: chroma_width,
+        stride: stride.div_ceil(2),
+        rows: chroma_rows,
+      };
+      vec![luma, chroma(), chroma()]
+    }
+    _ => vec![luma],
+  }
+}
+
+/// Check that every row of every plane fits within its stride
+pub(crate) fn fits(planes: &[Plane]) -> bool {
+  planes.iter().all(|plane| plane.stride >= plane.row_bytes)
+}
+
+/// Total of `bytes(plane) * rows` over the planes, or `None` if it overflows
+fn total_bytes(planes: &[Plane], bytes: impl Fn(&Plane) -> usize) -> Option<usize> {
+  planes.iter().try_fold(0usize, |total, plane| {
+    bytes(plane)
+      .checked_mul(plane.rows)
+      .and_then(|size| total.checked_add(size))
+  })
+}
+
+/// Allocate a zeroed buffer, or `None` if it is too large for the address space or the allocator
+fn zeroed(len: usize) -> Option<Vec<u8>> {
+  let mut output = Vec::new();
+  output.try_reserve_exact(len).ok()?;
+  output.resize(len, 0);
+  Some(output)
+}
+
+/// Copy the rows of a padded buffer into a tightly packed one. The final row of the image
+/// doesn't need to include its padding
+pub(crate) fn unpad(data: &[u8], planes: &[Plane]) -> Option<Vec<u8>> {
+  let mut output = Vec::new();
+  output
+    .try_reserve_exact(total_bytes(planes, |plane| plane.row_bytes)?)
+    .ok()?;
+  let mut offset = 0usize;
+
+  for plane in planes {
+    for _ in 0..plane.rows {
+      output.extend_from_slice(data.get(offset..offset.checked_add(plane.row_bytes)?)?);
+      offset = offset.checked_add(plane.stride)?;
+    }
+  }
+
+  Some(output)
+}
+
+/// Bytes spanned by the planes, up to the end of the final row, or `None` if it overflows
+pub(crate) fn span(planes: &[Plane]) -> Option<usize> {
+  let total = total_bytes(planes, |plane| plane.stride)?;
+  let trailing = planes
+    .last()
+    .filter(|plane| plane.rows > 0)
+    .map_or(0, |plane| plane.stride - plane.row_bytes);
+
+  Some(total - trailing)
+}
+
+/// Check whether the planes have no padding between rows
+pub(crate) fn is_packed(planes: &[Plane]) -> bool {
+  planes.iter().all(|plane| plane.stride == plane.row_bytes)
+}
+
+/// Copy the rows of a tightly packed buffer to `target` at their stride, leaving the padding
+/// untouched. Each row is written through its own pointer, and no other bytes are accessed.
+///
+/// # Safety
+///
+/// `target` must be valid for writes of `span` bytes, and the rows must not be accessed by
+/// anything else while they are written
+pub(crate) unsafe fn write(data: &[u8], planes: &[Plane], target: *mut u8) {
+  let mut input_offset = 0;
+  let mut output_offset = 0;
+
+  for plane in planes {
+    for _ in 0..plane.rows {
+      let row = &data[input_offset..input_offset + plane.row_bytes];
+      unsafe {
+        std::ptr::copy_nonoverlapping(row.as_ptr(), target.add(output_offset), row.len());
+      }
+      input_offset += plane.row_bytes;
+      output_offset += plane.stride;
+    }
+  }
+}
+
+/// Space the rows of a tightly packed buffer out to their stride, filling the padding with zeros.
+/// Returns `None` if the padded buffer can't be allocated
+pub(crate) fn pad(data: &[u8], planes: &[Plane]) -> Option<Vec<u8>> {
+  let mut output = zeroed(total_bytes(planes, |plane| plane.stride)?)?;
+  // SAFETY: the output is at least `span` bytes long
+  unsafe { write(data, planes, output.as_mut_ptr()) };
+  Some(output)
+}
@@ -0,0 +1,167 @@
+// Text rasterisation for the `drawText` transform step
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScaleFont, ScaleFont, point};
+use image::DynamicImage;
+
+use crate::blend::blend_pixel;
+use crate::{BlendMode, TextAlign, TextOp, TextVerticalAlign};
+
+/// Width of a single line of text, including kerning.
+fn measure_line(font: &PxScaleFont<&FontArc>, text: &str) -> f32 {
+  let mut width = 0.0;
+  let mut previous: Option<GlyphId> = None;
+
+  for c in text.chars() {
+    let id = font.glyph_id(c);
+    if let Some(previous) = previous {
+      width += font.kern(previous, id);
+    }
+    width += font.h_advance(id);
+    previous = Some(id);
+  }
+
+  width
+}
+
+/// Split the text into lines on explicit newlines and, when a maximum width is given,
+/// greedily wrap on whitespace. Words wider than the box are broken between characters.
+fn layout_lines(font: &PxScaleFont<&FontArc>, text: &str, max_width: Option<f32>) -> Vec<String> {
+  let mut lines = Vec::new();
+
+  for paragraph in text.split('\n') {
+    let paragraph = paragraph.strip_suffix('\r').unwrap_or(paragraph);
+
+    let Some(max_width) = max_width else {
+      lines.push(paragraph.to_string());
+      continue;
+    };
+
+    let mut line = String::new();
+    for word in paragraph.split_whitespace() {
+      let candidate = if line.is_empty() {
+        word.to_string()
+      } else {
+        format!("{line} {word}")
+      };
+
+      if measure_line(font, &candidate) <= max_width {
+        line = candidate;
+        continue;
+      }
+
+      if !line.is_empty() {
+        lines.push(std::mem::take(&mut line));
+      }
+
+      for c in word.chars() {
+        line.push(c);
+        if line.chars().count() > 1 && measure_line(font, &line) > max_width {
+          line.pop();
+          lines.push(std::mem::take(&mut line));
+          line.push(c);
+        }
+      }
+    }
+    lines.push(line);
+  }
+
+  lines
+}
+
+pub(crate) fn draw_text(img: DynamicImage, op: &TextOp) -> DynamicImage {
+  let mut canvas = img.into_rgba8();
+
+  let box_width = op
+    .width
+    .map(i64::from)
+    .unwrap_or((canvas.width() as i64).saturating_sub(op.x))
+    .max(0);
+  let box_height = op
+    .height
+    .map(i64::from)
+    .unwrap_or((canvas.height() as i64).saturating_sub(op.y))
+    .max(0);
+
+  // Only pixels inside both the text box and the image are touched. The coordinates come straight
+  // from JS, so can be anywhere in the i64 range
+  let clip_left = op.x.max(0);
+  let clip_top = op.y.max(0);
+  let clip_right = op.x.saturating_add(box_width).min(canvas.width() as i64);
+  let clip_bottom = op.y.saturating_add(box_height).min(canvas.height() as i64);
+
+  let font = op.font.as_scaled(op.size);
+
+  let max_width = if op.wrap {
+    Some(box_width as f32)
+  } else {
+    None
+  };
+  let lines = layout_lines(&font, &op.text, max_width);
+
+  let line_advance = (font.height() + font.line_gap()) * op.line_height;
+  let block_height = font.height() + line_advance * (lines.len().saturating_sub(1)) as f32;
+
+  let top = op.y as f32
+    + match op.vertical_align {
+      TextVerticalAlign::Top => 0.0,
+      TextVerticalAlign::Center => (box_height as f32 - block_height) / 2.0,
+      TextVerticalAlign::Bottom => box_height as f32 - block_height,
+    };
+
+  for (i, line) in lines.iter().enumerate() {
+    let baseline = top + font.ascent() + line_advance * i as f32;
+
+    let line_width = measure_line(&font, line);
+    let mut caret = op.x as f32
+      + match op.align {
+        TextAlign::Left => 0.0,
+        TextAlign::Center => (box_width as f32 - line_width) / 2.0,
+        TextAlign::Right => box_width as f32 - line_width,
+      };
+
+    let mut previous: Option<GlyphId> = None;
+    for c in line.chars() {
+      let id = font.glyph_id(c);
+      if let Some(previous) = previous {
+        caret += font.kern(previous, id);
+      }
+      previous = Some(id);
+
+      let glyph = id.with_scale_and_position(font.scale(), point(caret, baseline));
+      caret += font.h_advance(id);
+
+      let Some(outlined) = font.outline_glyph(glyph) else {
+        // Whitespace and missing glyphs have no outline
+        continue;
+      };
+
+      // Rasterising is the expensive part, so skip glyphs that are entirely clipped
+      let bounds = outlined.px_bounds();
+      if bounds.max.x as i64 <= clip_left
+        || bounds.min.x as i64 >= clip_right
+        || bounds.max.y as i64 <= clip_top
+        || bounds.min.y as i64 >= clip_bottom
+      {
+        continue;
+      }
+
+      outlined.draw(|gx, gy, coverage| {
+        let px = bounds.min.x as i64 + gx as i64;
+        let py = bounds.min.y as i64 + gy as i64;
+        if px < clip_left || px >= clip_right || py < clip_top || py >= clip_bottom {
+          return;
+        }
+
+        let pixel = canvas.get_pixel_mut(px as u32, py as u32);
+        *pixel = blend_pixel(
+          BlendMode::Normal,
+          *pixel,
+          op.color,
+          coverage.clamp(0.0, 1.0),
+        );
+      });
+    }
+  }
+
+  DynamicImage::from(canvas)
+}
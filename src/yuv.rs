@@ -0,0 +1,234 @@
+// YUV conversion for video frames
+
+use image::{Rgb, RgbImage};
+
+use crate::{YuvMatrix, YuvRange};
+
+#[derive(Clone, Copy)]
+pub(crate) enum YuvLayout {
+  /// Full size Y plane, followed by a half size plane of interleaved U and V
+  Nv12,
+  /// Full size Y plane, followed by half size U and V planes
+  I420,
+  /// Packed Y0 U Y1 V, with each pair of pixels sharing chroma
+  Yuyv,
+  /// Packed U Y0 V Y1, with each pair of pixels sharing chroma
+  Uyvy,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct YuvColorSpace {
+  pub matrix: YuvMatrix,
+  pub range: YuvRange,
+}
+
+impl Default for YuvColorSpace {
+  fn default() -> Self {
+    YuvColorSpace {
+      matrix: YuvMatrix::Bt601,
+      range: YuvRange::Limited,
+    }
+  }
+}
+
+/// Factors for converting between one colour space and rgb, worked out once per image
+struct Conversion {
+  kr: f32,
+  kb: f32,
+  kg: f32,
+  luma_offset: f32,
+  /// Scale from coded luma to the 0 to 255 range
+  luma_scale: f32,
+  /// Scale from coded chroma to the -128 to 128 range
+  chroma_scale: f32,
+}
+
+impl Conversion {
+  fn new(space: YuvColorSpace) -> Self {
+    let (kr, kb) = match space.matrix {
+      YuvMatrix::Bt601 => (0.299, 0.114),
+      YuvMatrix::Bt709 => (0.2126, 0.0722),
+    };
+
+    // Limited range puts black at 16, white at 235 and chroma between 16 and 240
+    let (luma_offset, luma_scale, chroma_scale) = match space.range {
+      YuvRange::Limited => (16.0, 255.0 / 219.0, 255.0 / 224.0),
+      YuvRange::Full => (0.0, 1.0, 1.0),
+    };
+
+    Conversion {
+      kr,
+      kb,
+      kg: 1.0 - kr - kb,
+      luma_offset,
+      luma_scale,
+      chroma_scale,
+    }
+  }
+
+  fn to_rgb(&self, y: u8, u: u8, v: u8) -> Rgb<u8> {
+    let y = (y as f32 - self.luma_offset) * self.luma_scale;
+    let u = (u as f32 - 128.0) * self.chroma_scale;
+    let v = (v as f32 - 128.0) * self.chroma_scale;
+
+    let red = y + 2.0 * (1.0 - self.kr) * v;
+    let blue = y + 2.0 * (1.0 - self.kb) * u;
+    let green = (y - self.kr * red - self.kb * blue) / self.kg;
+
+    Rgb([clamp(red), clamp(green), clamp(blue)])
+  }
+
+  /// Luma of a pixel, before scaling into the coded range
+  fn luma(&self, pixel: &Rgb<u8>) -> f32 {
+    let [red, green, blue] = pixel.0.map(|value| value as f32);
+    self.kr * red + self.kg * green + self.kb * blue
+  }
+
+  /// Chroma of a pixel, before scaling into the coded range
+  fn chroma(&self, pixel: &Rgb<u8>) -> (f32, f32) {
+    let luma = self.luma(pixel);
+    let [red, _, blue] = pixel.0.map(|value| value as f32);
+    (
+      (blue - luma) / (2.0 * (1.0 - self.kb)),
+      (red - luma) / (2.0 * (1.0 - self.kr)),
+    )
+  }
+
+  fn code_luma(&self, luma: f32) -> u8 {
+    clamp(luma / self.luma_scale + self.luma_offset)
+  }
+
+  fn code_chroma(&self, chroma: f32) -> u8 {
+    clamp(chroma / self.chroma_scale + 128.0)
+  }
+}
+
+fn clamp(value: f32) -> u8 {
+  value.round().clamp(0.0, 255.0) as u8
+}
+
+/// Index of the y, u and v samples for a pixel
+fn sample_indices(
+  layout: YuvLayout,
+  width: usize,
+  height: usize,
+  x: usize,
+  y: usize,
+) -> (usize, usize, usize) {
+  let chroma_w = width.div_ceil(2);
+
+  match layout {
+    YuvLayout::Nv12 => {
+      let chroma = width * height + (y / 2) * chroma_w * 2 + (x / 2) * 2;
+      (y * width + x, chroma, chroma + 1)
+    }
+    YuvLayout::I420 => {
+      let u_plane = width * height;
+      let v_plane = u_plane + chroma_w * height.div_ceil(2);
+      let chroma = (y / 2) * chroma_w + x / 2;
+      (y * width + x, u_plane + chroma, v_plane + chroma)
+    }
+    YuvLayout::Yuyv => {
+      let pair = (y * chroma_w + x / 2) * 4;
+      (pair + (x % 2) * 2, pair + 1, pair + 3)
+    }
+    YuvLayout::Uyvy => {
+      let pair = (y * chroma_w + x / 2) * 4;
+      (pair + 1 + (x % 2) * 2, pair, pair + 2)
+    }
+  }
+}
+
+/// Convert a YUV frame to rgb. Chroma is shared between each 2x2 block of pixels for the
+/// planar layouts, or each horizontal pair for the packed layouts, with odd sizes rounded up
+pub(crate) fn to_rgb(
+  data: &[u8],
+  width: u32,
+  height: u32,
+  layout: YuvLayout,
+  space: YuvColorSpace,
+) -> Option<RgbImage> {
+  let (w, h) = (width as usize, height as usize);
+  let (chroma_w, chroma_h) = (w.div_ceil(2), h.div_ceil(2));
+  let conversion = Conversion::new(space);
+
+  let frame_size = match layout {
+    YuvLayout::Nv12 | YuvLayout::I420 => w * h + chroma_w * chroma_h * 2,
+    YuvLayout::Yuyv | YuvLayout::Uyvy => chroma_w * 4 * h,
+  };
+  if data.len() < frame_size {
+    return None;
+  }
+
+  Some(RgbImage::from_fn(width, height, |x, y| {
+    let (y_index, u_index, v_index) = sample_indices(layout, w, h, x as usize, y as usize);
+    conversion.to_rgb(data[y_index], data[u_index], data[v_index])
+  }))
+}
+
+/// Convert an rgb image to a YUV frame. Chroma is averaged over each block of pixels that
+/// shares a sample, rather than taken from a single pixel
+pub(crate) fn from_rgb(img: &RgbImage, layout: YuvLayout, space: YuvColorSpace) -> Vec<u8> {
+  let (width, height) = img.dimensions();
+  let (w, h) = (width as usize, height as usize);
+  let conversion = Conversion::new(space);
+
+  // Planar layouts share chroma over 2x2 blocks, and packed layouts over horizontal pairs
+  let block_h = match layout {
+    YuvLayout::Nv12 | YuvLayout::I420 => 2,
+    YuvLayout::Yuyv | YuvLayout::Uyvy => 1,
+  };
+  let (chroma_w, chroma_h) = (w.div_ceil(2), h.div_ceil(block_h));
+
+  let mut chroma = Vec::with_capacity(chroma_w * chroma_h);
+  for cy in 0..chroma_h {
+    for cx in 0..chroma_w {
+      let mut sum = (0.0, 0.0);
+      let mut count = 0.0;
+      for y in (cy * block_h)..((cy + 1) * block_h).min(h) {
+        for x in (cx * 2)..(cx * 2 + 2).min(w) {
+          let (u, v) = conversion.chroma(img.get_pixel(x as u32, y as u32));
+          sum = (sum.0 + u, sum.1 + v);
+          count += 1.0;
+        }
+      }
+      chroma.push((
+        conversion.code_chroma(sum.0 / count),
+        conversion.code_chroma(sum.1 / count),
+      ));
+    }
+  }
+
+  let luma_plane = || {
+    img
+      .pixels()
+      .map(|pixel| conversion.code_luma(conversion.luma(pixel)))
+  };
+
+  // Each pair of pixels as [y0, u, y1, v], with an odd final pixel repeated to fill the pair
+  let pairs = || {
+    let (conversion, chroma) = (&conversion, &chroma);
+    (0..h).flat_map(move |y| {
+      (0..chroma_w).map(move |cx| {
+        let luma = |x: usize| {
+          let pixel = img.get_pixel(x.min(w - 1) as u32, y as u32);
+          conversion.code_luma(conversion.luma(pixel))
+        };
+        let (u, v) = chroma[y * chroma_w + cx];
+        [luma(cx * 2), u, luma(cx * 2 + 1), v]
+      })
+    })
+  };
+
+  match layout {
+    YuvLayout::Nv12 => luma_plane()
+      .chain(chroma.iter().flat_map(|(u, v)| [*u, *v]))
+      .collect(),
+    YuvLayout::I420 => luma_plane()
+      .chain(chroma.iter().map(|(u, _)| *u))
+      .chain(chroma.iter().map(|(_, v)| *v))
+      .collect(),
+    YuvLayout::Yuyv => pairs().flatten().collect(),
+    YuvLayout::Uyvy => pairs().flat_map(|[y0, u, y1, v]| [u, y0, v, y1]).collect(),
+  }
+}